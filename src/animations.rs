@@ -4,6 +4,7 @@ use crate::{
     player::{Player, PlayerState},
     resources::CursorPos,
     state::GameState,
+    status::{StatusEffects, StatusKind},
 };
 use bevy::prelude::*;
 
@@ -63,13 +64,23 @@ fn animate_player(
 }
 
 fn animate_enemy(
-    mut enemy_query: Query<(&mut TextureAtlas, &EnemyState, &AnimationTimer), With<Enemy>>,
+    mut enemy_query: Query<
+        (
+            &mut TextureAtlas,
+            &EnemyState,
+            &AnimationTimer,
+            &StatusEffects,
+        ),
+        With<Enemy>,
+    >,
 ) {
-    for (mut atlas, state, timer) in enemy_query.iter_mut() {
-        if timer.just_finished() {
+    for (mut atlas, state, timer, status) in enemy_query.iter_mut() {
+        if timer.just_finished() && !status.has(StatusKind::Freeze) {
             let base_sprite_index = match state {
-                EnemyState::Idle => 0,
-                EnemyState::Run => 4,
+                EnemyState::Idle | EnemyState::WindUp => 0,
+                EnemyState::Run | EnemyState::Attack => 4,
+                // Hold the current frame while stunned
+                EnemyState::Stunned => continue,
             };
             atlas.index = base_sprite_index + ((atlas.index + 1) % 4);
         }
@@ -107,12 +118,11 @@ fn flip_gun_sprite_x(
     cursor_pos: Res<CursorPos>,
     mut gun_query: Query<(&mut Sprite, &Transform), With<Gun>>,
 ) {
-    if gun_query.is_empty() {
+    let Some(cursor_pos) = cursor_pos.0 else {
         return;
-    }
+    };
 
-    let (mut sprite, transform) = gun_query.single_mut();
-    if let Some(cursor_pos) = cursor_pos.0 {
+    for (mut sprite, transform) in gun_query.iter_mut() {
         sprite.flip_x = cursor_pos.x <= transform.translation.x;
     }
 }
@@ -0,0 +1,454 @@
+use crate::{
+    collision::CollisionLayer,
+    constants::*,
+    damage::{Damage, DamageType},
+    enemy::{Enemy, EnemyState, EnemyType},
+    flowfield::FlowField,
+    gun::{spawn_bullet, Bullet},
+    player::Player,
+    resources::GameResourceSpriteAtlas,
+    state::GameState,
+    status::{OnHitStatus, StatusEffect, StatusEffects, StatusKind},
+};
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// How an enemy moves and attacks. Each variant is driven by its own system.
+#[derive(Component)]
+pub enum EnemyBehavior {
+    Chase,
+    /// Closes in, winds up, then lunges in a straight line and is briefly stunned afterwards.
+    Charge {
+        timer: Timer,
+        direction: Vec2,
+    },
+    /// Circles the player at `radius`.
+    Orbit {
+        radius: f32,
+        orbit_direction: f32,
+    },
+    /// Chases until its health drops below `threshold` of its maximum, then runs away.
+    FleeWhenHurt {
+        threshold: f32,
+    },
+    /// Ambles around until the player comes within `aggro_radius`, then turns into a chaser.
+    Wander {
+        target: Vec2,
+        retarget: Timer,
+        aggro_radius: f32,
+    },
+    /// Keeps its distance from the player, strafing around them and firing projectiles.
+    Ranged {
+        preferred_distance: f32,
+        strafe_direction: f32,
+        fire_timer: Timer,
+    },
+}
+
+impl EnemyBehavior {
+    /// Picks a behaviour for a freshly spawned enemy, weighted by its type.
+    pub fn roll_for(enemy_type: &EnemyType, position: Vec2) -> Self {
+        let mut rng = rand::thread_rng();
+        let roll = rng.gen_range(0.0..1.0);
+        match enemy_type {
+            EnemyType::Grub if roll < 0.5 => Self::wander(position),
+            EnemyType::Skele if roll < 0.3 => Self::FleeWhenHurt {
+                threshold: FLEE_HEALTH_THRESHOLD,
+            },
+            EnemyType::Gob if roll < 0.4 => Self::Orbit {
+                radius: rng.gen_range(ORBIT_MIN_RADIUS..ORBIT_MAX_RADIUS),
+                orbit_direction: random_sign(),
+            },
+            EnemyType::Devil if roll < 0.1 => Self::ranged(),
+            EnemyType::Demon if roll < 0.3 => Self::Charge {
+                timer: Timer::from_seconds(CHARGE_WIND_UP, TimerMode::Once),
+                direction: Vec2::ZERO,
+            },
+            _ => Self::Chase,
+        }
+    }
+
+    pub fn ranged() -> Self {
+        let mut rng = rand::thread_rng();
+        Self::Ranged {
+            preferred_distance: rng.gen_range(RANGED_ENEMY_MIN_DISTANCE..RANGED_ENEMY_MAX_DISTANCE),
+            strafe_direction: random_sign(),
+            fire_timer: Timer::from_seconds(
+                rng.gen_range(RANGED_ENEMY_FIRE_RATE * 0.5..RANGED_ENEMY_FIRE_RATE * 1.5),
+                TimerMode::Repeating,
+            ),
+        }
+    }
+
+    pub fn wander(position: Vec2) -> Self {
+        Self::Wander {
+            target: random_point_near(position),
+            retarget: Timer::from_seconds(WANDER_RETARGET_RATE, TimerMode::Repeating),
+            aggro_radius: WANDER_AGGRO_RADIUS,
+        }
+    }
+}
+
+pub struct EnemyBehaviorPlugin;
+
+impl Plugin for EnemyBehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                approach_player,
+                charge_behavior,
+                orbit_behavior,
+                flee_when_hurt_behavior,
+                wander_behavior,
+                ranged_behavior,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn random_sign() -> f32 {
+    if rand::thread_rng().gen_bool(0.5) {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn random_point_near(position: Vec2) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    let angle = rng.gen_range(0.0..2.0 * PI);
+    position + Vec2::from_angle(angle) * rng.gen_range(0.0..WANDER_RADIUS)
+}
+
+/// Steers towards a ring of `radius` around the player while circling it.
+fn ring_direction(to_player: Vec2, distance: f32, radius: f32, circle_direction: f32) -> Vec2 {
+    let radial = ((distance - radius) / RING_DISTANCE_TOLERANCE).clamp(-1.0, 1.0);
+    let tangent = to_player.perp() * circle_direction * RING_TANGENT_FACTOR;
+    (to_player * radial + tangent).normalize_or_zero()
+}
+
+/// Heading towards the player, following the flow field around obstacles until close enough
+/// to walk straight at them.
+fn chase_direction(flow_field: &FlowField, position: Vec2, player_position: Vec2) -> Vec2 {
+    let offset = player_position - position;
+    if offset.length() < FLOW_FIELD_DIRECT_DISTANCE {
+        return offset.normalize_or_zero();
+    }
+    flow_field
+        .direction(position)
+        .unwrap_or_else(|| offset.normalize_or_zero())
+}
+
+fn player_position(player_query: &Query<&Transform, With<Player>>) -> Option<Vec2> {
+    player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate())
+}
+
+#[allow(clippy::type_complexity)]
+fn approach_player(
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    flow_field: Res<FlowField>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, behavior, mut state, status) in &mut enemy_query {
+        if !matches!(behavior, EnemyBehavior::Chase) {
+            continue;
+        }
+        let speed = enemy.speed * status.speed_multiplier();
+        let enemy_translation = transform.translation.truncate();
+        let direction = chase_direction(&flow_field, enemy_translation, player_translation)
+            * speed
+            * time.delta_seconds();
+        transform.translation += direction.extend(0.0);
+        *state = EnemyState::Run;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn charge_behavior(
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &mut EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    flow_field: Res<FlowField>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, mut behavior, mut state, status) in &mut enemy_query {
+        let EnemyBehavior::Charge { timer, direction } = behavior.as_mut() else {
+            continue;
+        };
+        let speed = enemy.speed * status.speed_multiplier();
+        let offset = player_translation - transform.translation.truncate();
+
+        match *state {
+            EnemyState::WindUp => {
+                timer.tick(time.delta());
+                if timer.finished() {
+                    *direction = offset.normalize_or_zero();
+                    *timer = Timer::from_seconds(CHARGE_DURATION, TimerMode::Once);
+                    *state = EnemyState::Attack;
+                }
+            }
+            EnemyState::Attack => {
+                timer.tick(time.delta());
+                transform.translation +=
+                    (*direction * CHARGE_SPEED * status.speed_multiplier() * time.delta_seconds())
+                        .extend(0.0);
+                if timer.finished() {
+                    *timer = Timer::from_seconds(CHARGE_RECOVERY, TimerMode::Once);
+                    *state = EnemyState::Stunned;
+                }
+            }
+            EnemyState::Stunned => {
+                timer.tick(time.delta());
+                if timer.finished() {
+                    *state = EnemyState::Run;
+                }
+            }
+            _ => {
+                if offset.length() < CHARGE_RANGE {
+                    *timer = Timer::from_seconds(CHARGE_WIND_UP, TimerMode::Once);
+                    *state = EnemyState::WindUp;
+                } else {
+                    let position = transform.translation.truncate();
+                    let direction = chase_direction(&flow_field, position, player_translation);
+                    transform.translation += (direction * speed * time.delta_seconds()).extend(0.0);
+                    *state = EnemyState::Run;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn orbit_behavior(
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    flow_field: Res<FlowField>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, behavior, mut state, status) in &mut enemy_query {
+        let EnemyBehavior::Orbit {
+            radius,
+            orbit_direction,
+        } = behavior
+        else {
+            continue;
+        };
+        let speed = enemy.speed * status.speed_multiplier();
+        let position = transform.translation.truncate();
+        let offset = player_translation - position;
+        let to_player = chase_direction(&flow_field, position, player_translation);
+        if to_player == Vec2::ZERO {
+            continue;
+        }
+
+        let direction = ring_direction(to_player, offset.length(), *radius, *orbit_direction);
+        transform.translation += (direction * speed * time.delta_seconds()).extend(0.0);
+        *state = EnemyState::Run;
+    }
+}
+
+fn flee_when_hurt_behavior(
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    flow_field: Res<FlowField>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, behavior, mut state, status) in &mut enemy_query {
+        let EnemyBehavior::FleeWhenHurt { threshold } = behavior else {
+            continue;
+        };
+        let speed = enemy.speed * status.speed_multiplier();
+        let position = transform.translation.truncate();
+        // Fleeing backs away along the path the enemy would have taken in
+        let mut direction = chase_direction(&flow_field, position, player_translation);
+        if enemy.health < enemy.max_health * threshold {
+            direction = -direction;
+        }
+        transform.translation += (direction * speed * time.delta_seconds()).extend(0.0);
+        *state = EnemyState::Run;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn wander_behavior(
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &mut EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, mut behavior, mut state, status) in &mut enemy_query {
+        let EnemyBehavior::Wander {
+            target,
+            retarget,
+            aggro_radius,
+        } = behavior.as_mut()
+        else {
+            continue;
+        };
+        let speed = enemy.speed * status.speed_multiplier();
+        let position = transform.translation.truncate();
+        if position.distance(player_translation) < *aggro_radius {
+            *behavior = EnemyBehavior::Chase;
+            continue;
+        }
+
+        retarget.tick(time.delta());
+        if retarget.just_finished() {
+            *target = random_point_near(position);
+        }
+
+        let offset = *target - position;
+        if offset.length() < WANDER_ARRIVE_DISTANCE {
+            *state = EnemyState::Idle;
+            continue;
+        }
+        transform.translation +=
+            (offset.normalize() * speed * WANDER_SPEED_FACTOR * time.delta_seconds()).extend(0.0);
+        *state = EnemyState::Run;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn ranged_behavior(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    mut enemy_query: Query<
+        (
+            &mut Transform,
+            &Enemy,
+            &mut EnemyBehavior,
+            &mut EnemyState,
+            &StatusEffects,
+        ),
+        Without<Player>,
+    >,
+    player_query: Query<&Transform, With<Player>>,
+    flow_field: Res<FlowField>,
+    time: Res<Time>,
+) {
+    let Some(player_translation) = player_position(&player_query) else {
+        return;
+    };
+
+    for (mut transform, enemy, mut behavior, mut state, status) in enemy_query.iter_mut() {
+        let EnemyBehavior::Ranged {
+            preferred_distance,
+            strafe_direction,
+            fire_timer,
+        } = behavior.as_mut()
+        else {
+            continue;
+        };
+        let speed = enemy.speed * status.speed_multiplier();
+        let offset = player_translation - transform.translation.truncate();
+        let distance = offset.length();
+        let Some(to_player) = offset.try_normalize() else {
+            continue;
+        };
+
+        // Shots fly straight, but keeping range follows the flow field around obstacles
+        let path = chase_direction(
+            &flow_field,
+            transform.translation.truncate(),
+            player_translation,
+        );
+        let direction = ring_direction(path, distance, *preferred_distance, *strafe_direction);
+        transform.translation += (direction * speed * time.delta_seconds()).extend(0.0);
+        *state = EnemyState::Run;
+
+        fire_timer.tick(time.delta());
+        if fire_timer.just_finished()
+            && distance < RANGED_ENEMY_FIRE_RANGE
+            && !status.has(StatusKind::Freeze)
+        {
+            *state = EnemyState::Attack;
+            spawn_bullet(
+                &mut commands,
+                &game_resource,
+                transform.translation,
+                Bullet {
+                    on_hit: Some(OnHitStatus {
+                        effect: StatusEffect::slow(),
+                        chance: 1.0,
+                    }),
+                    ..Bullet::new(
+                        to_player,
+                        ENEMY_BULLET_SPEED,
+                        CollisionLayer::Enemy,
+                        Damage::new(ENEMY_BULLET_DAMAGE, DamageType::Ice),
+                    )
+                },
+            );
+        }
+    }
+}
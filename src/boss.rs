@@ -0,0 +1,334 @@
+use crate::{
+    behavior::EnemyBehavior,
+    camera::CameraTrauma,
+    collision::CollisionLayer,
+    constants::*,
+    damage::{Damage, DamageType},
+    enemy::{spawn_enemy, Enemy, EnemyState, EnemyType},
+    gun::{spawn_bullet, Bullet},
+    indicators::OffscreenIndicator,
+    minimap::MinimapMarker,
+    player::Player,
+    resources::*,
+    state::GameState,
+    status::StatusEffects,
+};
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::{PI, TAU};
+
+#[derive(Clone, Copy)]
+enum BossAttack {
+    RadialRing,
+    Summon,
+    Charge,
+}
+
+/// Attacks cycled through in each phase. A boss moves to the next phase every time its
+/// health drops below one of `BOSS_PHASE_THRESHOLDS`.
+const BOSS_PHASE_ATTACKS: [&[BossAttack]; 3] = [
+    &[BossAttack::RadialRing],
+    &[BossAttack::RadialRing, BossAttack::Summon],
+    &[
+        BossAttack::RadialRing,
+        BossAttack::Charge,
+        BossAttack::Summon,
+        BossAttack::Charge,
+    ],
+];
+
+#[derive(Component)]
+pub struct Boss {
+    pub phase: usize,
+    attack_timer: Timer,
+    next_attack: usize,
+    /// Drives wind-up, charge and recovery while the boss is not in `EnemyState::Run`.
+    state_timer: Timer,
+    charge_direction: Vec2,
+}
+
+impl Default for Boss {
+    fn default() -> Self {
+        Self {
+            phase: 0,
+            attack_timer: Timer::from_seconds(BOSS_ATTACK_RATE, TimerMode::Repeating),
+            next_attack: 0,
+            state_timer: Timer::from_seconds(0.0, TimerMode::Once),
+            charge_direction: Vec2::ZERO,
+        }
+    }
+}
+
+/// Death sequence played before the boss is despawned.
+#[derive(Component)]
+pub struct BossDeath(Timer);
+
+#[derive(Resource)]
+struct BossSchedule {
+    next_spawn_time: f32,
+}
+
+impl Default for BossSchedule {
+    fn default() -> Self {
+        Self {
+            next_spawn_time: BOSS_FIRST_SPAWN_TIME,
+        }
+    }
+}
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BossSchedule>()
+            .add_systems(OnEnter(GameState::GameInit), reset_boss_schedule)
+            .add_systems(
+                Update,
+                (
+                    spawn_scheduled_bosses,
+                    update_boss_phase,
+                    update_boss_attacks,
+                    start_boss_death,
+                    update_boss_death,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn reset_boss_schedule(mut schedule: ResMut<BossSchedule>) {
+    *schedule = BossSchedule::default();
+}
+
+fn spawn_scheduled_bosses(
+    mut commands: Commands,
+    mut schedule: ResMut<BossSchedule>,
+    run_stats: Res<RunStats>,
+    game_entities: Res<GameEntitySpriteAtlas>,
+    world_bounds: Res<WorldBounds>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if run_stats.elapsed < schedule.next_spawn_time || player_query.is_empty() {
+        return;
+    }
+    schedule.next_spawn_time += BOSS_SPAWN_INTERVAL;
+
+    let angle = rand::thread_rng().gen_range(0.0..TAU);
+    let position = world_bounds.clamp(
+        player_query.single().translation.truncate()
+            + Vec2::from_angle(angle) * BOSS_SPAWN_DISTANCE,
+    );
+    spawn_enemy(&mut commands, &game_entities, EnemyType::Demon, position).insert((
+        Transform::from_translation(position.extend(2.5))
+            .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * BOSS_SCALE)),
+        Enemy {
+            health: BOSS_HEALTH,
+            max_health: BOSS_HEALTH,
+            speed: BOSS_SPEED,
+            xp: BOSS_XP,
+        },
+        Boss::default(),
+        Name::new("Demon Lord"),
+        MinimapMarker {
+            color: Color::srgb(1.0, 0.1, 0.1),
+            size: 6,
+        },
+        OffscreenIndicator {
+            color: Color::srgb(1.0, 0.1, 0.1),
+        },
+    ));
+}
+
+fn update_boss_phase(mut boss_query: Query<(&Enemy, &mut Boss)>, mut trauma: ResMut<CameraTrauma>) {
+    for (enemy, mut boss) in boss_query.iter_mut() {
+        let fraction = enemy.health / enemy.max_health;
+        let phase = BOSS_PHASE_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| fraction < threshold)
+            .count();
+        if phase > boss.phase {
+            boss.phase = phase;
+            boss.next_attack = 0;
+            trauma.add(BOSS_PHASE_TRAUMA);
+        }
+    }
+}
+
+fn update_boss_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_entities: Res<GameEntitySpriteAtlas>,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut boss_query: Query<(
+        &mut Transform,
+        &Enemy,
+        &mut Boss,
+        &mut EnemyState,
+        &StatusEffects,
+    )>,
+    player_query: Query<&Transform, (With<Player>, Without<Boss>)>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+    let player_pos = player_query.single().translation.truncate();
+
+    for (mut transform, enemy, mut boss, mut state, status) in boss_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let to_player = (player_pos - position).normalize_or_zero();
+        boss.state_timer.tick(time.delta());
+
+        match *state {
+            EnemyState::WindUp => {
+                if boss.state_timer.finished() {
+                    boss.charge_direction = to_player;
+                    boss.state_timer = Timer::from_seconds(BOSS_CHARGE_DURATION, TimerMode::Once);
+                    *state = EnemyState::Attack;
+                }
+            }
+            EnemyState::Attack => {
+                transform.translation +=
+                    (boss.charge_direction * BOSS_CHARGE_SPEED * time.delta_seconds()).extend(0.0);
+                if boss.state_timer.finished() {
+                    // The charge ends in a stomp
+                    trauma.add(BOSS_STOMP_TRAUMA);
+                    spawn_ring(
+                        &mut commands,
+                        &game_resource,
+                        transform.translation,
+                        12,
+                        0.0,
+                    );
+                    boss.state_timer = Timer::from_seconds(BOSS_CHARGE_RECOVERY, TimerMode::Once);
+                    *state = EnemyState::Stunned;
+                }
+            }
+            EnemyState::Stunned => {
+                if boss.state_timer.finished() {
+                    *state = EnemyState::Run;
+                }
+            }
+            _ => {
+                transform.translation +=
+                    (to_player * enemy.speed * status.speed_multiplier() * time.delta_seconds())
+                        .extend(0.0);
+                *state = EnemyState::Run;
+
+                boss.attack_timer.tick(time.delta());
+                if !boss.attack_timer.just_finished() {
+                    continue;
+                }
+
+                let attacks = BOSS_PHASE_ATTACKS[boss.phase.min(BOSS_PHASE_ATTACKS.len() - 1)];
+                let attack = attacks[boss.next_attack % attacks.len()];
+                boss.next_attack += 1;
+                match attack {
+                    BossAttack::RadialRing => {
+                        let count = BOSS_RING_BULLETS + boss.phase * 8;
+                        let offset = time.elapsed_seconds();
+                        spawn_ring(
+                            &mut commands,
+                            &game_resource,
+                            transform.translation,
+                            count,
+                            offset,
+                        );
+                    }
+                    BossAttack::Summon => {
+                        let mut rng = rand::thread_rng();
+                        for _ in 0..BOSS_SUMMON_COUNT {
+                            let offset = Vec2::from_angle(rng.gen_range(0.0..TAU))
+                                * rng.gen_range(100.0..250.0);
+                            spawn_enemy(
+                                &mut commands,
+                                &game_entities,
+                                EnemyType::Grub,
+                                position + offset,
+                            )
+                            .insert(EnemyBehavior::Chase);
+                        }
+                    }
+                    BossAttack::Charge => {
+                        boss.state_timer =
+                            Timer::from_seconds(BOSS_CHARGE_WIND_UP, TimerMode::Once);
+                        *state = EnemyState::WindUp;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn spawn_ring(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    position: Vec3,
+    count: usize,
+    angle_offset: f32,
+) {
+    let step = 2.0 * PI / count as f32;
+    for i in 0..count {
+        spawn_bullet(
+            commands,
+            game_resource,
+            position,
+            Bullet::new(
+                Vec2::from_angle(angle_offset + step * i as f32),
+                ENEMY_BULLET_SPEED,
+                CollisionLayer::Enemy,
+                Damage::new(ENEMY_BULLET_DAMAGE, DamageType::Fire),
+            ),
+        );
+    }
+}
+
+fn start_boss_death(
+    mut commands: Commands,
+    boss_query: Query<(Entity, &Enemy), With<Boss>>,
+    mut trauma: ResMut<CameraTrauma>,
+) {
+    for (entity, enemy) in boss_query.iter() {
+        if enemy.health <= 0.0 {
+            commands
+                .entity(entity)
+                .remove::<Enemy>()
+                .insert(BossDeath(Timer::from_seconds(
+                    BOSS_DEATH_DURATION,
+                    TimerMode::Once,
+                )));
+            trauma.add(1.0);
+        }
+    }
+}
+
+fn update_boss_death(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut run_stats: ResMut<RunStats>,
+    mut player_xp: ResMut<PlayerExperience>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut boss_query: Query<(Entity, &mut BossDeath, &mut Sprite, &mut Transform)>,
+) {
+    for (entity, mut death, mut sprite, mut transform) in boss_query.iter_mut() {
+        death.0.tick(time.delta());
+        if death.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            run_stats.kills += 1;
+            player_xp.add(BOSS_XP);
+            continue;
+        }
+
+        // Flicker and shudder, fading out over the last part of the sequence
+        let progress = death.0.fraction();
+        let flicker = if ((progress * 20.0) as u32).is_multiple_of(2) {
+            10.0
+        } else {
+            1.0
+        };
+        let alpha = (1.0 - progress).min(0.3) / 0.3;
+        sprite.color = Color::srgba(flicker, flicker, flicker, alpha);
+        transform.rotation = Quat::from_rotation_z((progress * 60.0).sin() * 0.1);
+        trauma.add(BOSS_DEATH_TRAUMA * time.delta_seconds());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{player::Player, state::*};
+use crate::{
+    constants::*,
+    player::Player,
+    resources::{CursorPos, WorldBounds},
+    state::*,
+};
 use bevy::{math::*, prelude::*};
 
 use bevy_pancam::{PanCam, PanCamPlugin};
@@ -6,38 +11,200 @@ use bevy_pancam::{PanCam, PanCamPlugin};
 #[derive(Component)]
 pub struct MainCamera;
 
+/// Offset currently applied on top of the follow position, so it can be undone next frame.
+#[derive(Component, Default)]
+struct CameraShake {
+    offset: Vec2,
+}
+
+/// Accumulated screen shake in `[0, 1]`. Gameplay systems call `add` and it decays over time.
+#[derive(Resource, Default)]
+pub struct CameraTrauma {
+    value: f32,
+}
+
+impl CameraTrauma {
+    pub fn add(&mut self, amount: f32) {
+        self.value = (self.value + amount).clamp(0.0, 1.0);
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+#[derive(Resource)]
+pub struct FollowCameraSettings {
+    /// How quickly the camera closes the gap to its target, in 1/seconds.
+    pub smoothing: f32,
+    /// Fraction of the player-to-cursor vector the camera leads by.
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    /// Half extents of the box the target can move in without dragging the camera.
+    pub dead_zone: Vec2,
+}
+
+impl Default for FollowCameraSettings {
+    fn default() -> Self {
+        Self {
+            smoothing: CAMERA_SMOOTHING,
+            look_ahead: CAMERA_LOOK_AHEAD,
+            max_look_ahead: CAMERA_MAX_LOOK_AHEAD,
+            dead_zone: Vec2::new(CAMERA_DEAD_ZONE_W, CAMERA_DEAD_ZONE_H),
+        }
+    }
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub enum ScreenShakeSetting {
+    #[default]
+    Full,
+    Reduced,
+    Off,
+}
+
+impl ScreenShakeSetting {
+    pub fn intensity(&self) -> f32 {
+        match self {
+            ScreenShakeSetting::Full => 1.0,
+            ScreenShakeSetting::Reduced => 0.35,
+            ScreenShakeSetting::Off => 0.0,
+        }
+    }
+}
+
 pub struct FollowCameraPlugin;
 
 impl Plugin for FollowCameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(PanCamPlugin::default())
+            .init_resource::<FollowCameraSettings>()
+            .init_resource::<CameraTrauma>()
+            .init_resource::<ScreenShakeSetting>()
             .add_systems(OnEnter(GameState::Loading), setup_camera)
+            .add_systems(OnEnter(GameState::GameInit), reset_camera_trauma)
+            .add_systems(Update, cycle_screen_shake_setting)
             .add_systems(
                 Update,
-                camera_follow_player.run_if(in_state(GameState::InGame)),
+                (camera_follow_player, apply_camera_shake)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
             );
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn camera_follow_player(
+    time: Res<Time>,
+    settings: Res<FollowCameraSettings>,
+    cursor_pos: Res<CursorPos>,
+    world_bounds: Res<WorldBounds>,
     player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera>, Without<Player>)>,
+    mut camera_query: Query<
+        (&mut Transform, &CameraShake, &OrthographicProjection),
+        (With<MainCamera>, Without<Player>),
+    >,
 ) {
     if camera_query.is_empty() || player_query.is_empty() {
         return;
     }
 
-    let mut camera_transform = camera_query.single_mut();
-    let player_transform = player_query.single().translation;
+    let (mut camera_transform, shake, projection) = camera_query.single_mut();
+    let player_position = player_query.single().translation.truncate();
+    let anchor = camera_transform.translation.truncate() - shake.offset;
+
+    let look_ahead = cursor_pos.0.map_or(Vec2::ZERO, |cursor| {
+        ((cursor - player_position) * settings.look_ahead).clamp_length_max(settings.max_look_ahead)
+    });
+    let target = player_position + look_ahead;
 
-    let (x, y) = (player_transform.x, player_transform.y);
+    // Only chase the part of the target offset that sticks out of the dead zone
+    let offset = target - anchor;
+    let desired = target - offset.clamp(-settings.dead_zone, settings.dead_zone);
+
+    // Exponential decay keeps the follow speed independent of the frame rate
+    let blend = 1.0 - (-settings.smoothing * time.delta_seconds()).exp();
+    let position = anchor.lerp(desired, blend);
+    let position = world_bounds.half_size.map_or(position, |half_world| {
+        clamp_to_world(position, half_world, projection.area.half_size())
+    });
+
+    camera_transform.translation = position.extend(camera_transform.translation.z);
+}
+
+/// Keeps the view inside the world, centring it on an axis where the view is larger than the world.
+fn clamp_to_world(position: Vec2, half_world: Vec2, half_view: Vec2) -> Vec2 {
+    let clamp_axis = |value: f32, half_world: f32, half_view: f32| {
+        if half_view >= half_world {
+            0.0
+        } else {
+            value.clamp(-half_world + half_view, half_world - half_view)
+        }
+    };
+    vec2(
+        clamp_axis(position.x, half_world.x, half_view.x),
+        clamp_axis(position.y, half_world.y, half_view.y),
+    )
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    setting: Res<ScreenShakeSetting>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut camera_query: Query<(&mut Transform, &mut CameraShake), With<MainCamera>>,
+) {
+    if camera_query.is_empty() {
+        return;
+    }
+
+    trauma.value = (trauma.value - CAMERA_TRAUMA_DECAY * time.delta_seconds()).max(0.0);
+
+    // Squaring makes small amounts of trauma subtle and large amounts violent
+    let shake = trauma.value * trauma.value * setting.intensity();
+    let t = time.elapsed_seconds() * CAMERA_SHAKE_FREQUENCY;
+    let offset = vec2(smooth_noise(t, 0.0), smooth_noise(t, 1.0)) * CAMERA_SHAKE_MAX_OFFSET * shake;
+    let angle = smooth_noise(t, 2.0) * CAMERA_SHAKE_MAX_ANGLE * shake;
+
+    let (mut camera_transform, mut camera_shake) = camera_query.single_mut();
+    // `camera_follow_player` already removed last frame's offset from the translation
+    camera_transform.translation += offset.extend(0.0);
+    camera_transform.rotation = Quat::from_rotation_z(angle);
+    camera_shake.offset = offset;
+}
+
+fn cycle_screen_shake_setting(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut setting: ResMut<ScreenShakeSetting>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        *setting = match *setting {
+            ScreenShakeSetting::Full => ScreenShakeSetting::Reduced,
+            ScreenShakeSetting::Reduced => ScreenShakeSetting::Off,
+            ScreenShakeSetting::Off => ScreenShakeSetting::Full,
+        };
+    }
+}
+
+fn reset_camera_trauma(mut trauma: ResMut<CameraTrauma>) {
+    trauma.value = 0.0;
+}
 
-    camera_transform.translation = camera_transform.translation.lerp(vec3(x, y, 0.0), 0.1);
+/// Smoothly interpolated value noise in `[-1, 1]`, with `seed` selecting an independent channel.
+fn smooth_noise(t: f32, seed: f32) -> f32 {
+    let hash = |n: f32| ((n * 127.1 + seed * 311.7).sin() * 43758.547).rem_euclid(1.0) * 2.0 - 1.0;
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3.0 - 2.0 * f);
+    hash(i) + (hash(i + 1.0) - hash(i)) * f
 }
 
 fn setup_camera(mut commands: Commands) {
     commands
-        .spawn((Camera2dBundle::default(), MainCamera))
+        .spawn((
+            Camera2dBundle::default(),
+            MainCamera,
+            CameraShake::default(),
+        ))
         .insert(PanCam {
             grab_buttons: Vec::new(),
             min_scale: 0.5,
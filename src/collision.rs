@@ -1,14 +1,41 @@
-use crate::constants::{BULLET_DAMAGE, KD_TREE_REFRESH_RATE};
-use crate::player::{Player, PlayerEnemyCollisionEvent};
-use crate::{enemy::Enemy, gun::Bullet, state::GameState};
+use crate::constants::{
+    BOSS_CONTACT_DAMAGE, BOSS_HIT_RADIUS, ENEMY_BULLET_HIT_RADIUS, ENEMY_DAMAGE,
+    KD_TREE_REFRESH_RATE,
+};
+use crate::player::{Player, PlayerDamageEvent};
+use crate::status::ApplyStatusEvent;
+use crate::{
+    boss::Boss,
+    enemy::{Enemy, EnemyDamageEvent},
+    gun::{Bullet, BulletStop, BulletStopEvent},
+    state::GameState,
+};
 use bevy::utils::Duration;
 use bevy::{prelude::*, time::common_conditions::on_timer};
 use kd_tree::{KdPoint, KdTree};
+use rand::Rng;
+
+/// The side a projectile belongs to. Projectiles only collide with the opposing layer,
+/// so enemy fire never hits other enemies and player fire never hits the player.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CollisionLayer {
+    Player,
+    Enemy,
+}
 
 #[derive(Component)]
 pub struct Collidable {
-    pos: Vec2,
-    entity: Entity,
+    pub pos: Vec2,
+    pub entity: Entity,
+    /// How far the enemy's body reaches out from `pos`. Zero for regular enemies.
+    pub radius: f32,
+}
+
+impl Collidable {
+    /// Distance from `point` to the edge of the enemy's body.
+    pub fn distance_to(&self, point: Vec2) -> f32 {
+        (self.pos.distance(point) - self.radius).max(0.0)
+    }
 }
 
 impl KdPoint for Collidable {
@@ -22,12 +49,49 @@ impl KdPoint for Collidable {
     }
 }
 
+/// Spatial index of every live enemy, bosses included. Queries measure to the edge of each
+/// enemy's body, so large enemies are hit the same way by every system.
 #[derive(Resource)]
-struct EnemyKdTree(KdTree<Collidable>);
+pub struct EnemyKdTree {
+    tree: KdTree<Collidable>,
+    /// Largest body radius in the tree, used to widen the point queries.
+    max_radius: f32,
+}
 
 impl Default for EnemyKdTree {
     fn default() -> Self {
-        Self(KdTree::build_by_ordered_float(vec![]))
+        Self {
+            tree: KdTree::build_by_ordered_float(vec![]),
+            max_radius: 0.0,
+        }
+    }
+}
+
+impl EnemyKdTree {
+    pub fn iter(&self) -> std::slice::Iter<'_, Collidable> {
+        self.tree.iter()
+    }
+
+    /// Enemies whose body overlaps the circle of `radius` around `center`.
+    pub fn within_radius(&self, center: Vec2, radius: f32) -> Vec<&Collidable> {
+        self.tree
+            .within_radius(&[center.x, center.y], radius + self.max_radius)
+            .into_iter()
+            .filter(|enemy| enemy.distance_to(center) <= radius)
+            .collect()
+    }
+
+    /// The enemy whose body edge is closest to `center`.
+    pub fn nearest(&self, center: Vec2) -> Option<&Collidable> {
+        let nearest = self.tree.nearest(&[center.x, center.y])?;
+        // A larger body further away can still reach closer than the nearest centre
+        self.tree
+            .within_radius(
+                &[center.x, center.y],
+                nearest.squared_distance.sqrt() + self.max_radius,
+            )
+            .into_iter()
+            .min_by(|a, b| a.distance_to(center).total_cmp(&b.distance_to(center)))
     }
 }
 
@@ -40,6 +104,7 @@ impl Plugin for CollisionPlugin {
             (
                 handle_enemy_bullet_collision,
                 handle_enemy_player_collision,
+                handle_player_bullet_collision,
                 update_enemy_kd_tree
                     .run_if(on_timer(Duration::from_secs_f32(KD_TREE_REFRESH_RATE))),
             )
@@ -50,46 +115,116 @@ impl Plugin for CollisionPlugin {
 
 fn handle_enemy_player_collision(
     player_query: Query<&Transform, With<Player>>,
+    boss_query: Query<(), With<Boss>>,
     tree: Res<EnemyKdTree>,
-    mut ew: EventWriter<PlayerEnemyCollisionEvent>,
+    mut ew: EventWriter<PlayerDamageEvent>,
 ) {
     if player_query.is_empty() {
         return;
     }
 
-    let player_pos = player_query.single().translation;
-    let enemies = tree.0.within_radius(&[player_pos.x, player_pos.y], 50.0);
-    for _ in enemies.iter() {
-        ew.send(PlayerEnemyCollisionEvent);
+    let player_pos = player_query.single().translation.truncate();
+    let enemies = tree.within_radius(player_pos, 50.0);
+    for enemy in enemies.iter() {
+        let amount = if boss_query.contains(enemy.entity) {
+            BOSS_CONTACT_DAMAGE
+        } else {
+            ENEMY_DAMAGE
+        };
+        ew.send(PlayerDamageEvent { amount });
     }
 }
 
 fn update_enemy_kd_tree(
     mut tree: ResMut<EnemyKdTree>,
-    enemy_query: Query<(&Transform, Entity), With<Enemy>>,
+    enemy_query: Query<(&Transform, Entity, Has<Boss>), With<Enemy>>,
 ) {
     let mut items = Vec::new();
-    for (t, e) in enemy_query.iter() {
+    for (t, e, is_boss) in enemy_query.iter() {
         items.push(Collidable {
             entity: e,
             pos: t.translation.truncate(),
+            radius: if is_boss { BOSS_HIT_RADIUS } else { 0.0 },
         })
     }
 
-    tree.0 = KdTree::build_by_ordered_float(items);
+    tree.max_radius = items.iter().map(|item| item.radius).fold(0.0, f32::max);
+    tree.tree = KdTree::build_by_ordered_float(items);
+}
+
+fn handle_player_bullet_collision(
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    bullet_query: Query<(Entity, &Transform, &Bullet)>,
+    mut ew: EventWriter<PlayerDamageEvent>,
+    mut status_ew: EventWriter<ApplyStatusEvent>,
+    mut stop_ew: EventWriter<BulletStopEvent>,
+) {
+    if player_query.is_empty() {
+        return;
+    }
+
+    let (player, player_transform) = player_query.single();
+    let player_pos = player_transform.translation.truncate();
+    let mut rng = rand::thread_rng();
+    for (bullet_entity, bullet_transform, bullet) in bullet_query.iter() {
+        if bullet.layer != CollisionLayer::Enemy {
+            continue;
+        }
+        let bullet_pos = bullet_transform.translation.truncate();
+        if bullet_pos.distance(player_pos) < ENEMY_BULLET_HIT_RADIUS {
+            ew.send(PlayerDamageEvent {
+                amount: bullet.damage.amount,
+            });
+            if let Some(on_hit) = bullet.on_hit.filter(|on_hit| rng.gen_bool(on_hit.chance)) {
+                status_ew.send(ApplyStatusEvent {
+                    entity: player,
+                    effect: on_hit.effect,
+                });
+            }
+            stop_ew.send(BulletStopEvent {
+                entity: bullet_entity,
+                position: bullet_pos,
+                reason: BulletStop::Impact,
+            });
+        }
+    }
 }
 
 fn handle_enemy_bullet_collision(
-    bullet_query: Query<&Transform, With<Bullet>>,
+    bullet_query: Query<(Entity, &Transform, &Bullet)>,
     tree: Res<EnemyKdTree>,
-    mut enemy_query: Query<(&Transform, &mut Enemy), With<Enemy>>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+    mut status_ew: EventWriter<ApplyStatusEvent>,
+    mut stop_ew: EventWriter<BulletStopEvent>,
 ) {
-    for bullet_transform in bullet_query.iter() {
+    let mut rng = rand::thread_rng();
+    for (bullet_entity, bullet_transform, bullet) in bullet_query.iter() {
+        if bullet.layer != CollisionLayer::Player {
+            continue;
+        }
         let pos = bullet_transform.translation;
-        let enemies = tree.0.within_radius(&[pos.x, pos.y], 50.0);
+        let enemies = tree.within_radius(pos.truncate(), 50.0);
+        // Exploding and splitting bullets stop at the first enemy, all others pierce
+        if !enemies.is_empty() && (bullet.on_impact.is_some() || bullet.modifiers.splits > 0) {
+            stop_ew.send(BulletStopEvent {
+                entity: bullet_entity,
+                position: pos.truncate(),
+                reason: BulletStop::HitEnemy,
+            });
+        }
         for e in enemies {
-            if let Ok((_, mut enemy)) = enemy_query.get_mut(e.entity) {
-                enemy.health -= BULLET_DAMAGE;
+            let (amount, crit) = bullet.damage.roll();
+            ew.send(EnemyDamageEvent {
+                entity: e.entity,
+                amount,
+                damage_type: bullet.damage.damage_type,
+                crit,
+            });
+            if let Some(on_hit) = bullet.on_hit.filter(|on_hit| rng.gen_bool(on_hit.chance)) {
+                status_ew.send(ApplyStatusEvent {
+                    entity: e.entity,
+                    effect: on_hit.effect,
+                });
             }
         }
     }
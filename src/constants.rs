@@ -6,7 +6,11 @@ pub const WW: f32 = 1200.0;
 pub const WH: f32 = 700.0;
 
 pub const PLAYER_SPEED: f32 = 200.0;
+pub const PLAYER_MAX_HEALTH: f32 = 100.0;
+pub const XP_BASE_LEVEL_COST: f32 = 50.0;
+pub const XP_PER_KILL: f32 = 1.0;
 pub const BULLET_SPEED: f32 = 2000.0;
+pub const BULLET_LIFETIME: f32 = 2.0;
 pub const BULLET_DAMAGE: f32 = 25.0;
 pub const GUN_FIRE_RATE: f32 = 0.125;
 pub const NUM_BULLETS: u32 = 9;
@@ -14,13 +18,255 @@ pub const BULLET_RADIUS: f32 = PI / 6.0; // 30 deg
 
 pub const MAX_ENEMY_COUNT: usize = 100000;
 pub const ENEMY_SPAWN_RATE: f32 = 0.5;
+pub const SPAWN_MIN_PLAYER_DISTANCE: f32 = 1000.0;
+// Spawns stay within the loaded terrain, whose obstacles are already known
+pub const SPAWN_MAX_PLAYER_DISTANCE: f32 = TERRAIN_LOAD_RADIUS as f32 * TERRAIN_CHUNK_SIZE;
+pub const SPAWN_ATTEMPTS: usize = 10;
+pub const SPAWN_OFFSCREEN_MARGIN: f32 = 100.0;
 pub const ENEMY_SPEED: f32 = 100.0;
 pub const ENEMY_HEALTH: f32 = 100.0;
 pub const ENEMY_DAMAGE: f32 = 1.0;
+pub const ENEMY_BULLET_SPEED: f32 = 400.0;
+pub const ENEMY_BULLET_DAMAGE: f32 = 10.0;
+pub const ENEMY_BULLET_HIT_RADIUS: f32 = 30.0;
+pub const RANGED_ENEMY_MIN_DISTANCE: f32 = 300.0;
+pub const RANGED_ENEMY_MAX_DISTANCE: f32 = 500.0;
+pub const RANGED_ENEMY_FIRE_RATE: f32 = 2.0;
+pub const RANGED_ENEMY_FIRE_RANGE: f32 = 700.0;
+pub const RING_DISTANCE_TOLERANCE: f32 = 50.0;
+pub const RING_TANGENT_FACTOR: f32 = 0.5;
+pub const ORBIT_MIN_RADIUS: f32 = 200.0;
+pub const ORBIT_MAX_RADIUS: f32 = 400.0;
+pub const CHARGE_RANGE: f32 = 350.0;
+pub const CHARGE_WIND_UP: f32 = 0.6;
+pub const CHARGE_DURATION: f32 = 0.5;
+pub const CHARGE_RECOVERY: f32 = 0.8;
+pub const CHARGE_SPEED: f32 = 700.0;
+pub const FLEE_HEALTH_THRESHOLD: f32 = 0.3;
+pub const WANDER_RADIUS: f32 = 300.0;
+pub const WANDER_RETARGET_RATE: f32 = 3.0;
+pub const WANDER_ARRIVE_DISTANCE: f32 = 10.0;
+pub const WANDER_SPEED_FACTOR: f32 = 0.5;
+pub const WANDER_AGGRO_RADIUS: f32 = 800.0;
+pub const FLOW_FIELD_CELL_SIZE: f32 = 64.0;
+pub const FLOW_FIELD_SIZE: i32 = 160;
+/// Cells around the player repaired each time they cross into a new cell.
+pub const FLOW_FIELD_REPAIR_RADIUS: i32 = 24;
+pub const FLOW_FIELD_DIRECT_DISTANCE: f32 = 96.0;
+pub const FLOW_FIELD_DEBUG_RADIUS: i32 = 20;
+pub const BOSS_FIRST_SPAWN_TIME: f32 = 180.0;
+pub const BOSS_SPAWN_INTERVAL: f32 = 240.0;
+pub const BOSS_SPAWN_DISTANCE: f32 = 900.0;
+pub const BOSS_HEALTH: f32 = 20000.0;
+pub const BOSS_SCALE: f32 = 4.0;
+pub const BOSS_SPEED: f32 = 60.0;
+pub const BOSS_HIT_RADIUS: f32 = 90.0;
+pub const BOSS_CONTACT_DAMAGE: f32 = 5.0;
+pub const BOSS_XP: f32 = 200.0;
+pub const BOSS_PHASE_THRESHOLDS: [f32; 2] = [0.66, 0.33];
+pub const BOSS_ATTACK_RATE: f32 = 3.0;
+pub const BOSS_RING_BULLETS: usize = 16;
+pub const BOSS_SUMMON_COUNT: usize = 12;
+pub const BOSS_CHARGE_WIND_UP: f32 = 0.8;
+pub const BOSS_CHARGE_DURATION: f32 = 0.6;
+pub const BOSS_CHARGE_RECOVERY: f32 = 1.0;
+pub const BOSS_CHARGE_SPEED: f32 = 900.0;
+pub const BOSS_DEATH_DURATION: f32 = 2.5;
+pub const BOSS_PHASE_TRAUMA: f32 = 0.5;
+pub const BOSS_STOMP_TRAUMA: f32 = 0.6;
+pub const BOSS_DEATH_TRAUMA: f32 = 0.8;
+pub const ELITE_BASE_CHANCE: f64 = 0.002;
+pub const ELITE_CHANCE_PER_WAVE: f64 = 0.002;
+pub const ELITE_MAX_CHANCE: f64 = 0.05;
+pub const ELITE_WAVES_PER_AFFIX: usize = 3;
+pub const ELITE_MAX_AFFIXES: usize = 3;
+pub const ELITE_SCALE: f32 = 1.4;
+pub const ELITE_HEALTH_PER_AFFIX: f32 = 1.5;
+pub const ELITE_XP_MULTIPLIER: f32 = 5.0;
+pub const ELITE_FAST_SPEED_MULTIPLIER: f32 = 1.8;
+pub const ELITE_ARMOR_DAMAGE_TAKEN: f32 = 0.4;
+pub const ELITE_SHIELD_FRACTION: f32 = 1.0;
+pub const ELITE_REGEN_RATE: f32 = 0.1;
+pub const ELITE_SPLIT_COUNT: usize = 3;
+pub const ELITE_SPLIT_HEALTH_FRACTION: f32 = 0.4;
+pub const ELITE_EXPLOSION_RADIUS: f32 = 150.0;
+pub const ELITE_EXPLOSION_DAMAGE: f32 = 20.0;
+pub const ELITE_EXPLOSION_TRAUMA: f32 = 0.4;
+pub const WAVE_DURATION: f32 = 60.0;
+
+pub const DAMAGE_NUMBER_POOL_SIZE: usize = 256;
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 0.6;
+pub const HIT_FLASH_DURATION: f32 = 0.08;
+pub const DEATH_EFFECT_DURATION: f32 = 0.3;
+
+pub const CAMERA_SMOOTHING: f32 = 6.0;
+pub const CAMERA_LOOK_AHEAD: f32 = 0.25;
+pub const CAMERA_MAX_LOOK_AHEAD: f32 = 150.0;
+pub const CAMERA_DEAD_ZONE_W: f32 = 40.0;
+pub const CAMERA_DEAD_ZONE_H: f32 = 30.0;
+pub const CAMERA_TRAUMA_DECAY: f32 = 1.5;
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 25.0;
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 30.0;
+pub const CAMERA_SHAKE_MAX_ANGLE: f32 = 0.05;
+pub const GUN_FIRE_TRAUMA: f32 = 0.08;
+pub const PLAYER_HIT_TRAUMA: f32 = 0.02;
+
+pub const MINIMAP_RESOLUTION: (u32, u32) = (150, 100);
+pub const MINIMAP_SIZES: [f32; 3] = [150.0, 240.0, 360.0];
+pub const MINIMAP_REFRESH_RATE: f32 = 0.1;
+pub const MINIMAP_DENSITY_SATURATION: f32 = 20.0;
+
+pub const INDICATOR_SECTORS: usize = 16;
+pub const INDICATOR_CLUSTER_MIN: u32 = 25;
+pub const INDICATOR_MARGIN: f32 = 30.0;
+pub const INDICATOR_ARROW_LENGTH: f32 = 30.0;
+
+pub const STATUS_TICK_RATE: f32 = 0.5;
+pub const BURN_DURATION: f32 = 3.0;
+pub const BURN_DPS: f32 = 20.0;
+pub const POISON_DURATION: f32 = 5.0;
+pub const POISON_DPS: f32 = 8.0;
+pub const POISON_MAX_STACKS: u32 = 5;
+pub const SLOW_DURATION: f32 = 2.0;
+pub const SLOW_MULTIPLIER: f32 = 0.5;
+pub const FREEZE_DURATION: f32 = 1.5;
+pub const SHOTGUN_BURN_CHANCE: f64 = 0.05;
+pub const SHOTGUN_CRIT_CHANCE: f64 = 0.1;
+pub const SHOTGUN_CRIT_MULTIPLIER: f32 = 2.0;
+pub const RIFLE_FIRE_RATE: f32 = 0.3;
+pub const RIFLE_DAMAGE: f32 = 60.0;
+pub const RIFLE_BULLET_SPEED: f32 = 2800.0;
+pub const RIFLE_CRIT_CHANCE: f64 = 0.25;
+pub const RIFLE_CRIT_MULTIPLIER: f32 = 2.5;
+pub const SMG_FIRE_RATE: f32 = 0.06;
+pub const SMG_DAMAGE: f32 = 15.0;
+pub const SMG_SPREAD: f32 = PI / 24.0;
+pub const SMG_POISON_CHANCE: f64 = 0.1;
+pub const FROST_CANNON_FIRE_RATE: f32 = 0.5;
+pub const FROST_CANNON_DAMAGE: f32 = 40.0;
+pub const FROST_CANNON_BULLETS: u32 = 3;
+pub const FROST_CANNON_SPREAD: f32 = PI / 12.0;
+pub const FROST_CANNON_BULLET_SPEED: f32 = 1200.0;
+pub const FROST_CANNON_FREEZE_CHANCE: f64 = 0.2;
+pub const RIFLE_MAGAZINE_SIZE: u32 = 10;
+pub const RIFLE_RELOAD_TIME: f32 = 1.5;
+pub const RIFLE_MAX_RESERVE: u32 = 60;
+pub const SMG_MAGAZINE_SIZE: u32 = 40;
+pub const SMG_RELOAD_TIME: f32 = 1.8;
+pub const SMG_MAX_RESERVE: u32 = 240;
+pub const FROST_CANNON_MAGAZINE_SIZE: u32 = 4;
+pub const FROST_CANNON_RELOAD_TIME: f32 = 2.0;
+pub const FROST_CANNON_MAX_RESERVE: u32 = 24;
+pub const RAILGUN_FIRE_RATE: f32 = 0.8;
+pub const RAILGUN_DAMAGE: f32 = 150.0;
+pub const RAILGUN_RANGE: f32 = 1500.0;
+pub const RAILGUN_CRIT_CHANCE: f64 = 0.3;
+pub const RAILGUN_CRIT_MULTIPLIER: f32 = 3.0;
+pub const RAILGUN_MAGAZINE_SIZE: u32 = 5;
+pub const RAILGUN_RELOAD_TIME: f32 = 1.6;
+pub const RAILGUN_MAX_RESERVE: u32 = 30;
+pub const LASER_TICK_RATE: f32 = 0.1;
+pub const LASER_DPS: f32 = 120.0;
+pub const LASER_LENGTH: f32 = 600.0;
+pub const LASER_BURN_CHANCE: f64 = 0.02;
+pub const LASER_MAGAZINE_SIZE: u32 = 60;
+pub const LASER_RELOAD_TIME: f32 = 2.0;
+pub const LASER_MAX_RESERVE: u32 = 300;
+pub const ROCKET_FIRE_RATE: f32 = 1.0;
+pub const ROCKET_SPEED: f32 = 900.0;
+pub const ROCKET_DAMAGE: f32 = 50.0;
+pub const ROCKET_EXPLOSION_RADIUS: f32 = 220.0;
+pub const ROCKET_EXPLOSION_DAMAGE: f32 = 200.0;
+pub const ROCKET_KNOCKBACK: f32 = 900.0;
+pub const ROCKET_TRAUMA: f32 = 0.35;
+pub const ROCKET_MAGAZINE_SIZE: u32 = 3;
+pub const ROCKET_RELOAD_TIME: f32 = 2.2;
+pub const ROCKET_MAX_RESERVE: u32 = 18;
+pub const GRENADE_FIRE_RATE: f32 = 0.7;
+pub const GRENADE_SPEED: f32 = 600.0;
+pub const GRENADE_DAMAGE: f32 = 10.0;
+pub const GRENADE_FUSE: f32 = 0.9;
+pub const GRENADE_EXPLOSION_RADIUS: f32 = 280.0;
+pub const GRENADE_EXPLOSION_DAMAGE: f32 = 150.0;
+pub const GRENADE_KNOCKBACK: f32 = 1200.0;
+pub const GRENADE_TRAUMA: f32 = 0.3;
+pub const GRENADE_MAGAZINE_SIZE: u32 = 6;
+pub const GRENADE_RELOAD_TIME: f32 = 2.5;
+pub const GRENADE_MAX_RESERVE: u32 = 36;
+/// Fraction of an explosion's damage and knockback still dealt at the edge of its radius.
+pub const EXPLOSION_EDGE_FALLOFF: f32 = 0.25;
+pub const EXPLOSION_EFFECT_DURATION: f32 = 0.3;
+pub const KNOCKBACK_DECAY: f32 = 8.0;
+pub const HOMING_RANGE: f32 = 600.0;
+/// Radians per second a homing bullet may turn, per stack.
+pub const HOMING_TURN_RATE: f32 = 3.0;
+pub const SPLIT_COUNT: u32 = 3;
+pub const SPLIT_SPREAD: f32 = PI / 3.0;
+pub const SPLIT_DAMAGE_FRACTION: f32 = 0.5;
+pub const SPLIT_CLEARANCE: f32 = 60.0;
+pub const PASSIVE_MAX_LEVEL: u32 = 5;
+pub const ORBIT_BLADE_DISTANCE: f32 = 120.0;
+/// Radians per second the blades circle the player at.
+pub const ORBIT_BLADE_SPEED: f32 = 3.0;
+pub const ORBIT_BLADE_HIT_RADIUS: f32 = 45.0;
+pub const ORBIT_BLADE_HIT_INTERVAL: f32 = 0.25;
+pub const ORBIT_BLADE_DAMAGE: f32 = 30.0;
+pub const ORBIT_BLADE_DAMAGE_PER_LEVEL: f32 = 10.0;
+pub const AURA_RADIUS: f32 = 150.0;
+pub const AURA_RADIUS_PER_LEVEL: f32 = 25.0;
+pub const AURA_TICK_RATE: f32 = 0.5;
+pub const AURA_DAMAGE: f32 = 10.0;
+pub const AURA_DAMAGE_PER_LEVEL: f32 = 5.0;
+pub const TURRET_RANGE: f32 = 700.0;
+pub const TURRET_FIRE_RATE: f32 = 0.8;
+/// Each turret level multiplies the time between shots by this.
+pub const TURRET_FIRE_RATE_SCALING: f32 = 0.85;
+pub const TURRET_DAMAGE: f32 = 30.0;
+pub const TURRET_DAMAGE_PER_LEVEL: f32 = 10.0;
+pub const TURRET_OFFSET: f32 = 60.0;
+pub const MELEE_COOLDOWN: f32 = 0.6;
+pub const MELEE_RANGE: f32 = 150.0;
+/// Full angle of the cone in front of the player a swing reaches.
+pub const MELEE_ARC: f32 = PI * 2.0 / 3.0;
+pub const MELEE_DAMAGE: f32 = 60.0;
+pub const MELEE_KNOCKBACK: f32 = 800.0;
+pub const MELEE_SWING_DURATION: f32 = 0.15;
+pub const MELEE_TRAUMA: f32 = 0.1;
+pub const HITSCAN_HIT_RADIUS: f32 = 40.0;
+pub const TRACER_LIFETIME: f32 = 0.15;
+pub const TRACER_WIDTH: f32 = 4.0;
+pub const BEAM_WIDTH: f32 = 12.0;
+pub const RELOAD_RING_RADIUS: f32 = 14.0;
+pub const RELOAD_RING_OFFSET: f32 = 40.0;
+
+pub const MAX_WEAPONS: usize = 4;
+pub const WEAPON_DROP_CHANCE: f64 = 0.005;
+pub const ELITE_WEAPON_DROP_CHANCE: f64 = 0.25;
+pub const PICKUP_RADIUS: f32 = 60.0;
+pub const PICKUP_LIFETIME: f32 = 30.0;
+pub const AMMO_DROP_CHANCE: f64 = 0.02;
+/// Share of each weapon's maximum reserve restored by an ammo pickup.
+pub const AMMO_PICKUP_REFILL: f32 = 0.5;
 
 pub const KD_TREE_REFRESH_RATE: f32 = 0.1;
 
-pub const NUM_WORLD_DECORATIONS: usize = 1000;
+pub const TERRAIN_TILE_PIXELS: i32 = 8;
+pub const TERRAIN_CHUNK_TILES: i32 = 16;
+pub const TERRAIN_PIXEL_SCALE: f32 = 6.0;
+pub const TERRAIN_TILE_SIZE: f32 = TERRAIN_TILE_PIXELS as f32 * TERRAIN_PIXEL_SCALE;
+pub const TERRAIN_CHUNK_SIZE: f32 = TERRAIN_TILE_SIZE * TERRAIN_CHUNK_TILES as f32;
+pub const TERRAIN_NOISE_SCALE: f32 = 1200.0;
+pub const TERRAIN_DIRT_THRESHOLD: f32 = 0.42;
+pub const TERRAIN_STONE_THRESHOLD: f32 = 0.6;
+pub const TERRAIN_LOAD_RADIUS: i32 = 3;
+pub const TERRAIN_UNLOAD_RADIUS: i32 = 5;
+pub const TERRAIN_CHUNKS_PER_FRAME: usize = 2;
+pub const OBSTACLE_SPAWN_CLEARANCE: f32 = 400.0;
+pub const OBSTACLE_GRID_CELL_SIZE: f32 = 256.0;
+pub const PLAYER_COLLISION_RADIUS: f32 = 20.0;
+pub const ENEMY_COLLISION_RADIUS: f32 = 20.0;
+pub const MAP_FILES: [&str; 2] = ["maps/arena.map.ron", "maps/grove.map.ron"];
+
 pub const WORLD_W: f32 = 6000.0;
 pub const WORLD_H: f32 = 4000.0;
 
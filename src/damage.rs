@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Ice,
+    Poison,
+}
+
+impl DamageType {
+    /// Colour used for damage numbers of this type.
+    pub fn color(&self) -> Color {
+        match self {
+            DamageType::Physical => Color::WHITE,
+            DamageType::Fire => Color::srgb(1.0, 0.55, 0.2),
+            DamageType::Ice => Color::srgb(0.55, 0.85, 1.0),
+            DamageType::Poison => Color::srgb(0.55, 1.0, 0.35),
+        }
+    }
+}
+
+/// Fraction of incoming damage ignored per damage type. Negative values are weaknesses.
+#[derive(Clone, Copy, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub ice: f32,
+    pub poison: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Ice => self.ice,
+            DamageType::Poison => self.poison,
+        }
+    }
+
+    pub fn apply(&self, amount: f32, damage_type: DamageType) -> f32 {
+        amount * (1.0 - self.get(damage_type))
+    }
+}
+
+/// Damage dealt by a single hit from a weapon or projectile.
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub amount: f32,
+    pub damage_type: DamageType,
+    pub crit_chance: f64,
+    pub crit_multiplier: f32,
+}
+
+impl Damage {
+    /// Damage that never crits, used for enemy attacks and damage over time.
+    pub fn new(amount: f32, damage_type: DamageType) -> Self {
+        Self {
+            amount,
+            damage_type,
+            crit_chance: 0.0,
+            crit_multiplier: 1.0,
+        }
+    }
+
+    /// Rolls for a critical hit, returning the damage dealt and whether it crit.
+    pub fn roll(&self) -> (f32, bool) {
+        if rand::thread_rng().gen_bool(self.crit_chance) {
+            (self.amount * self.crit_multiplier, true)
+        } else {
+            (self.amount, false)
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    constants::*,
+    damage::DamageType,
+    enemy::{Enemy, EnemyDamageEvent, EnemyDamageTakenEvent},
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::{math::vec3, prelude::*};
+use rand::Rng;
+
+#[derive(Component)]
+pub struct DamageNumber {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+pub struct HitFlash(pub Timer);
+
+/// Sprite colour to return to once a hit flash ends. Entities without one go back to white.
+#[derive(Component)]
+pub struct BaseTint(pub Color);
+
+#[derive(Component)]
+pub struct DeathEffect(pub Timer);
+
+/// Damage numbers are recycled round-robin so heavy fire never spawns new text entities.
+#[derive(Resource, Default)]
+struct DamageNumberPool {
+    entities: Vec<Entity>,
+    next: usize,
+}
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageNumberPool>()
+            .add_systems(OnEnter(GameState::GameInit), spawn_damage_number_pool)
+            .add_systems(
+                Update,
+                (
+                    show_damage_numbers,
+                    update_damage_numbers,
+                    start_hit_flash,
+                    update_hit_flash,
+                    update_death_effects,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_damage_number_pool(mut commands: Commands, mut pool: ResMut<DamageNumberPool>) {
+    pool.entities.clear();
+    pool.next = 0;
+    for _ in 0..DAMAGE_NUMBER_POOL_SIZE {
+        let entity = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section("", TextStyle::default()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                DamageNumber {
+                    velocity: Vec2::ZERO,
+                    lifetime: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, TimerMode::Once),
+                },
+                GameEntity,
+            ))
+            .id();
+        pool.entities.push(entity);
+    }
+}
+
+fn show_damage_numbers(
+    mut events: EventReader<EnemyDamageTakenEvent>,
+    mut pool: ResMut<DamageNumberPool>,
+    // Killing blows are shown too, so the target may already have lost `Enemy`
+    target_query: Query<&Transform, Without<DamageNumber>>,
+    mut number_query: Query<(
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+) {
+    if pool.entities.is_empty() {
+        events.clear();
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        let Ok(target) = target_query.get(event.entity) else {
+            continue;
+        };
+
+        let entity = pool.entities[pool.next];
+        pool.next = (pool.next + 1) % pool.entities.len();
+        let Ok((mut text, mut transform, mut visibility, mut number)) =
+            number_query.get_mut(entity)
+        else {
+            continue;
+        };
+
+        // Crits get a bigger font and a "!"
+        let amount = event.amount;
+        let color = match (event.crit, event.damage_type) {
+            (true, DamageType::Physical) => Color::srgb(1.0, 0.85, 0.1),
+            (_, damage_type) => damage_type.color(),
+        };
+        let (value, font_size) = if event.crit {
+            (format!("{:.0}!", amount), 36.0)
+        } else {
+            (format!("{:.0}", amount), 24.0)
+        };
+        text.sections[0].value = value;
+        text.sections[0].style = TextStyle {
+            font_size,
+            color,
+            ..default()
+        };
+        transform.translation = vec3(
+            target.translation.x + rng.gen_range(-10.0..10.0),
+            target.translation.y + 20.0,
+            10.0,
+        );
+        *visibility = Visibility::Visible;
+        number.velocity = Vec2::new(rng.gen_range(-20.0..20.0), 80.0);
+        number.lifetime.reset();
+    }
+}
+
+fn update_damage_numbers(
+    time: Res<Time>,
+    mut number_query: Query<(
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+        &mut DamageNumber,
+    )>,
+) {
+    for (mut text, mut transform, mut visibility, mut number) in number_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        number.lifetime.tick(time.delta());
+        if number.lifetime.finished() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        transform.translation += number.velocity.extend(0.0) * time.delta_seconds();
+        let alpha = 1.0 - number.lifetime.fraction();
+        text.sections[0].style.color.set_alpha(alpha);
+    }
+}
+
+fn start_hit_flash(
+    mut commands: Commands,
+    mut events: EventReader<EnemyDamageEvent>,
+    mut sprite_query: Query<&mut Sprite, With<Enemy>>,
+) {
+    for event in events.read() {
+        if let Ok(mut sprite) = sprite_query.get_mut(event.entity) {
+            // Sprite colors multiply the texture, so overdrive the channels to wash it out white
+            sprite.color = Color::srgb(10.0, 10.0, 10.0);
+            commands
+                .entity(event.entity)
+                .insert(HitFlash(Timer::from_seconds(
+                    HIT_FLASH_DURATION,
+                    TimerMode::Once,
+                )));
+        }
+    }
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut Sprite, &mut HitFlash, Option<&BaseTint>)>,
+) {
+    for (entity, mut sprite, mut flash, tint) in flash_query.iter_mut() {
+        flash.0.tick(time.delta());
+        if flash.0.finished() {
+            sprite.color = tint.map_or(Color::WHITE, |tint| tint.0);
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
+
+fn update_death_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dying_query: Query<(Entity, &mut Sprite, &mut Transform, &mut DeathEffect)>,
+) {
+    for (entity, mut sprite, mut transform, mut effect) in dying_query.iter_mut() {
+        effect.0.tick(time.delta());
+        if effect.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let remaining = 1.0 - effect.0.fraction();
+        sprite.color = Color::srgba(1.0, 0.3, 0.3, remaining);
+        transform.scale = Vec3::splat(SPRITE_SCALE_FACTOR * (0.5 + 0.5 * remaining));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    behavior::EnemyBehavior,
+    camera::CameraTrauma,
+    constants::*,
+    effects::{BaseTint, DeathEffect},
+    enemy::{spawn_enemy, Enemy, EnemyType},
+    indicators::OffscreenIndicator,
+    minimap::MinimapMarker,
+    player::{Player, PlayerDamageEvent},
+    resources::GameEntitySpriteAtlas,
+    state::GameState,
+};
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use rand::{seq::SliceRandom, Rng};
+use std::f32::consts::TAU;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EliteAffix {
+    Fast,
+    Armored,
+    Splitting,
+    Explosive,
+    Regenerating,
+    Shielded,
+}
+
+const ALL_AFFIXES: [EliteAffix; 6] = [
+    EliteAffix::Fast,
+    EliteAffix::Armored,
+    EliteAffix::Splitting,
+    EliteAffix::Explosive,
+    EliteAffix::Regenerating,
+    EliteAffix::Shielded,
+];
+
+impl EliteAffix {
+    fn tint(&self) -> Color {
+        match self {
+            EliteAffix::Fast => Color::srgb(0.6, 1.0, 0.6),
+            EliteAffix::Armored => Color::srgb(0.6, 0.6, 0.7),
+            EliteAffix::Splitting => Color::srgb(0.9, 0.5, 1.0),
+            EliteAffix::Explosive => Color::srgb(1.0, 0.5, 0.2),
+            EliteAffix::Regenerating => Color::srgb(0.4, 1.0, 0.8),
+            EliteAffix::Shielded => Color::srgb(0.5, 0.7, 1.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Elite {
+    pub affixes: Vec<EliteAffix>,
+    /// Damage absorbed before health is touched, only non-zero for `Shielded` elites.
+    pub shield: f32,
+}
+
+impl Elite {
+    /// Rolls whether a new enemy becomes an elite. Later waves roll more often and stack more
+    /// affixes.
+    pub fn roll(wave: u32) -> Option<Self> {
+        let mut rng = rand::thread_rng();
+        let chance =
+            (ELITE_BASE_CHANCE + ELITE_CHANCE_PER_WAVE * wave as f64).min(ELITE_MAX_CHANCE);
+        if !rng.gen_bool(chance) {
+            return None;
+        }
+
+        let max_affixes = (1 + wave as usize / ELITE_WAVES_PER_AFFIX).min(ELITE_MAX_AFFIXES);
+        let count = rng.gen_range(1..=max_affixes);
+        let affixes: Vec<_> = ALL_AFFIXES
+            .choose_multiple(&mut rng, count)
+            .copied()
+            .collect();
+        let shield = if affixes.contains(&EliteAffix::Shielded) {
+            ENEMY_HEALTH * ELITE_SHIELD_FRACTION
+        } else {
+            0.0
+        };
+        Some(Self { affixes, shield })
+    }
+
+    pub fn has(&self, affix: EliteAffix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
+    /// Multiplier applied to incoming damage.
+    pub fn damage_taken(&self) -> f32 {
+        if self.has(EliteAffix::Armored) {
+            ELITE_ARMOR_DAMAGE_TAKEN
+        } else {
+            1.0
+        }
+    }
+
+    /// Scales base stats and rewards, then tags the entity so it stands out in the world and on
+    /// the HUD.
+    pub fn apply(self, entity: &mut EntityCommands, position: Vec2) {
+        let tier = self.affixes.len() as f32;
+        let mut enemy = Enemy::default();
+        enemy.max_health *= 1.0 + ELITE_HEALTH_PER_AFFIX * tier;
+        enemy.health = enemy.max_health;
+        enemy.xp *= ELITE_XP_MULTIPLIER * tier;
+        if self.has(EliteAffix::Fast) {
+            enemy.speed *= ELITE_FAST_SPEED_MULTIPLIER;
+        }
+
+        // Blend the affix colours so multi-affix elites read as a mix
+        let tint = self
+            .affixes
+            .iter()
+            .map(|affix| affix.tint().to_linear())
+            .fold(LinearRgba::NONE, |acc, color| acc + color * (1.0 / tier));
+        let tint = Color::from(tint.with_alpha(1.0));
+
+        entity.insert((
+            enemy,
+            Transform::from_translation(position.extend(2.0))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * ELITE_SCALE)),
+            Sprite {
+                color: tint,
+                ..default()
+            },
+            BaseTint(tint),
+            OffscreenIndicator {
+                color: Color::srgb(1.0, 0.85, 0.1),
+            },
+            MinimapMarker {
+                color: Color::srgb(1.0, 0.85, 0.1),
+                size: 3,
+            },
+            self,
+        ));
+    }
+}
+
+pub struct ElitePlugin;
+
+impl Plugin for ElitePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (regenerate_elites, handle_elite_deaths).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn regenerate_elites(time: Res<Time>, mut elite_query: Query<(&mut Enemy, &Elite)>) {
+    for (mut enemy, elite) in elite_query.iter_mut() {
+        if elite.has(EliteAffix::Regenerating) {
+            let heal = enemy.max_health * ELITE_REGEN_RATE * time.delta_seconds();
+            enemy.health = (enemy.health + heal).min(enemy.max_health);
+        }
+    }
+}
+
+fn handle_elite_deaths(
+    mut commands: Commands,
+    game_entities: Res<GameEntitySpriteAtlas>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut ew: EventWriter<PlayerDamageEvent>,
+    dead_query: Query<(&Transform, &Elite, &EnemyType), Added<DeathEffect>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player_pos = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+
+    for (transform, elite, enemy_type) in dead_query.iter() {
+        let position = transform.translation.truncate();
+
+        if elite.has(EliteAffix::Splitting) {
+            let mut rng = rand::thread_rng();
+            for _ in 0..ELITE_SPLIT_COUNT {
+                let offset = Vec2::from_angle(rng.gen_range(0.0..TAU)) * 30.0;
+                let mut enemy = Enemy::default();
+                enemy.max_health *= ELITE_SPLIT_HEALTH_FRACTION;
+                enemy.health = enemy.max_health;
+                spawn_enemy(
+                    &mut commands,
+                    &game_entities,
+                    *enemy_type,
+                    position + offset,
+                )
+                .insert((enemy, EnemyBehavior::Chase));
+            }
+        }
+
+        if elite.has(EliteAffix::Explosive) {
+            trauma.add(ELITE_EXPLOSION_TRAUMA);
+            if player_pos.is_some_and(|player| player.distance(position) < ELITE_EXPLOSION_RADIUS) {
+                ew.send(PlayerDamageEvent {
+                    amount: ELITE_EXPLOSION_DAMAGE,
+                });
+            }
+        }
+    }
+}
@@ -1,30 +1,64 @@
 use crate::animations::AnimationTimer;
-use crate::constants::{
-    ENEMY_HEALTH, ENEMY_SPAWN_RATE, ENEMY_SPEED, MAX_ENEMY_COUNT, SPRITE_SCALE_FACTOR, WORLD_H,
-    WORLD_W,
-};
+use crate::behavior::EnemyBehavior;
+use crate::boss::Boss;
+use crate::camera::MainCamera;
+use crate::constants::*;
+use crate::damage::{DamageType, Resistances};
+use crate::effects::DeathEffect;
+use crate::elite::Elite;
+use crate::obstacle::ObstacleGrid;
 use crate::player::Player;
-use crate::resources::GameEntitySpriteAtlas;
+use crate::resources::{GameEntitySpriteAtlas, PlayerExperience, RunStats, WorldBounds};
+use crate::spawn_zone::{SpawnRules, SpawnZone, SpawnedBy};
 use crate::state::GameState;
+use crate::status::StatusEffects;
 use crate::world::GameEntity;
-use bevy::{math::vec3, prelude::*};
+use bevy::{ecs::system::EntityCommands, math::vec3, prelude::*};
 use rand::Rng;
-use std::f32::consts::PI;
 
 #[derive(Component)]
 pub struct Enemy {
     pub health: f32,
+    pub max_health: f32,
+    pub speed: f32,
+    /// Experience granted to the player on kill.
+    pub xp: f32,
 }
 
 impl Default for Enemy {
     fn default() -> Self {
         Self {
             health: ENEMY_HEALTH,
+            max_health: ENEMY_HEALTH,
+            speed: ENEMY_SPEED,
+            xp: XP_PER_KILL,
         }
     }
 }
 
+/// Velocity an enemy is being shoved with, e.g. by an explosion.
 #[derive(Component)]
+pub struct Knockback(pub Vec2);
+
+#[derive(Event)]
+pub struct EnemyDamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+    pub damage_type: DamageType,
+    pub crit: bool,
+}
+
+/// Damage an enemy actually took from an `EnemyDamageEvent`, after resistances, elite armor and
+/// shields.
+#[derive(Event)]
+pub struct EnemyDamageTakenEvent {
+    pub entity: Entity,
+    pub amount: f32,
+    pub damage_type: DamageType,
+    pub crit: bool,
+}
+
+#[derive(Component, Clone, Copy)]
 pub enum EnemyType {
     Grub = 1,
     Skele = 2,
@@ -33,11 +67,14 @@ pub enum EnemyType {
     Demon = 5,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq)]
 pub enum EnemyState {
     Idle,
     #[default]
     Run,
+    Attack,
+    WindUp,
+    Stunned,
 }
 
 impl EnemyType {
@@ -61,6 +98,34 @@ impl EnemyType {
             EnemyType::Demon => 5,
         }
     }
+
+    pub fn resistances(&self) -> Resistances {
+        match self {
+            EnemyType::Grub => Resistances {
+                fire: -0.25,
+                poison: 0.5,
+                ..default()
+            },
+            EnemyType::Skele => Resistances {
+                physical: 0.25,
+                ice: 0.25,
+                poison: 1.0,
+                ..default()
+            },
+            EnemyType::Gob => Resistances::default(),
+            EnemyType::Devil => Resistances {
+                fire: 0.75,
+                ice: -0.5,
+                ..default()
+            },
+            EnemyType::Demon => Resistances {
+                physical: 0.1,
+                fire: 0.5,
+                ice: -0.25,
+                ..default()
+            },
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -70,98 +135,184 @@ pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(SpawnTimer(Timer::from_seconds(
-            ENEMY_SPAWN_RATE,
-            TimerMode::Once,
-        )))
-        .add_systems(
-            Update,
-            (spawn_enemies, approach_player, despawn_dead_enemies)
-                .run_if(in_state(GameState::InGame)),
-        );
+        app.add_event::<EnemyDamageEvent>()
+            .add_event::<EnemyDamageTakenEvent>()
+            .insert_resource(SpawnTimer(Timer::from_seconds(
+                ENEMY_SPAWN_RATE,
+                TimerMode::Once,
+            )))
+            .add_systems(
+                Update,
+                (
+                    spawn_enemies,
+                    apply_enemy_damage,
+                    despawn_dead_enemies,
+                    apply_knockback,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_enemies(
     mut commands: Commands,
     game_entities: Res<GameEntitySpriteAtlas>,
     player_query: Query<&Transform, With<Player>>,
-    enemy_query: Query<&Transform, (With<Enemy>, Without<Player>)>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    enemy_query: Query<Option<&SpawnedBy>, With<Enemy>>,
+    zone_query: Query<(Entity, &Transform, &SpawnZone)>,
+    obstacle_grid: Res<ObstacleGrid>,
+    world_bounds: Res<WorldBounds>,
     mut spawn_timer: ResMut<SpawnTimer>,
+    run_stats: Res<RunStats>,
     time: Res<Time>,
 ) {
     spawn_timer.0.tick(time.delta());
     if spawn_timer.0.finished() {
         let num_enemies = enemy_query.iter().len();
-        if num_enemies >= MAX_ENEMY_COUNT || player_query.is_empty() {
+        if num_enemies >= MAX_ENEMY_COUNT || player_query.is_empty() || camera_query.is_empty() {
             return;
         }
         let player_pos = player_query.single().translation.truncate();
+        let (camera_transform, projection) = camera_query.single();
+        let rules = SpawnRules {
+            player_position: player_pos,
+            view: Rect::from_center_half_size(
+                camera_transform.translation.truncate(),
+                projection.area.half_size(),
+            ),
+            world_bounds: &world_bounds,
+            obstacles: &obstacle_grid,
+        };
+
+        // Zones with how many of their enemies are alive, kept up to date as this batch spawns
+        let mut zones: Vec<_> = zone_query
+            .iter()
+            .map(|(entity, transform, zone)| {
+                let alive = enemy_query
+                    .iter()
+                    .filter(|spawned_by| spawned_by.is_some_and(|s| s.0 == entity))
+                    .count();
+                (entity, transform.translation.truncate(), *zone, alive)
+            })
+            .collect();
+
+        let mut rng = rand::thread_rng();
         let enemies_to_spawn = (MAX_ENEMY_COUNT - num_enemies).min(1000);
         let enemy_type_to_spawn = EnemyType::get_random_enemy_type();
         for _ in 0..enemies_to_spawn {
-            let (x, y) = get_random_position_around(player_pos);
-            commands.spawn((
-                SpriteBundle {
-                    texture: game_entities.entity_sheets
-                        [enemy_type_to_spawn.get_sprite_sheet_index()]
-                    .clone()
-                    .unwrap(),
-                    transform: Transform::from_translation(vec3(x, y, 2.0))
-                        .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-                    ..default()
-                },
-                TextureAtlas {
-                    layout: game_entities.atlas_layout.clone().unwrap(),
-                    index: 0,
-                },
-                Enemy::default(),
-                EnemyState::default(),
-                AnimationTimer(Timer::from_seconds(0.125, TimerMode::Repeating)),
-                GameEntity,
-            ));
+            let Some((position, zone)) = rules.pick(&mut rng, &zones) else {
+                continue;
+            };
+            let mut enemy =
+                spawn_enemy(&mut commands, &game_entities, enemy_type_to_spawn, position);
+            enemy.insert(EnemyBehavior::roll_for(&enemy_type_to_spawn, position));
+            if let Some(zone) = zone {
+                enemy.insert(SpawnedBy(zone));
+                if let Some(entry) = zones.iter_mut().find(|(entity, ..)| *entity == zone) {
+                    entry.3 += 1;
+                }
+            }
+            if let Some(elite) = Elite::roll(run_stats.wave) {
+                elite.apply(&mut enemy, position);
+            }
         }
         spawn_timer.0.reset();
     }
 }
 
-fn get_random_position_around(pos: Vec2) -> (f32, f32) {
-    let mut rng = rand::thread_rng();
-    let angle = rng.gen_range(0.0..2.0 * PI);
-    let dist = rng.gen_range(1000.0..5000.0);
-
-    let offset_x = angle.cos() * dist;
-    let offset_y = angle.sin() * dist;
-
-    let random_x = pos.x + offset_x;
-    let random_y = pos.y + offset_y;
-
-    (random_x, random_y)
+/// Spawns a bare enemy of `enemy_type`. Callers add a behaviour or other components on top.
+pub fn spawn_enemy<'a>(
+    commands: &'a mut Commands,
+    game_entities: &GameEntitySpriteAtlas,
+    enemy_type: EnemyType,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        SpriteBundle {
+            texture: game_entities.entity_sheets[enemy_type.get_sprite_sheet_index()]
+                .clone()
+                .unwrap(),
+            transform: Transform::from_translation(vec3(position.x, position.y, 2.0))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: game_entities.atlas_layout.clone().unwrap(),
+            index: 0,
+        },
+        Enemy::default(),
+        enemy_type,
+        EnemyState::default(),
+        StatusEffects::default(),
+        AnimationTimer(Timer::from_seconds(0.125, TimerMode::Repeating)),
+        GameEntity,
+    ))
 }
 
-fn despawn_dead_enemies(mut commands: Commands, enemy_query: Query<(&Enemy, Entity), With<Enemy>>) {
-    for (enemy, entity) in enemy_query.iter() {
-        if enemy.health <= 0.0 {
-            commands.entity(entity).despawn();
+fn apply_enemy_damage(
+    mut events: EventReader<EnemyDamageEvent>,
+    mut enemy_query: Query<(&mut Enemy, &EnemyType, Option<&mut Elite>)>,
+    mut taken_ew: EventWriter<EnemyDamageTakenEvent>,
+) {
+    for event in events.read() {
+        let Ok((mut enemy, enemy_type, elite)) = enemy_query.get_mut(event.entity) else {
+            continue;
+        };
+        let mut amount = enemy_type
+            .resistances()
+            .apply(event.amount, event.damage_type);
+        if let Some(mut elite) = elite {
+            amount *= elite.damage_taken();
+            let absorbed = amount.min(elite.shield);
+            elite.shield -= absorbed;
+            amount -= absorbed;
         }
+        enemy.health -= amount;
+        taken_ew.send(EnemyDamageTakenEvent {
+            entity: event.entity,
+            amount,
+            damage_type: event.damage_type,
+            crit: event.crit,
+        });
     }
 }
 
-fn approach_player(
-    mut enemy_query: Query<&mut Transform, (With<Enemy>, Without<Player>)>,
-    player_query: Query<&Transform, With<Player>>,
+/// Pushes an enemy on top of whatever its behaviour does, fading out over time.
+fn apply_knockback(
+    mut commands: Commands,
     time: Res<Time>,
+    mut enemy_query: Query<(Entity, &mut Transform, &mut Knockback)>,
 ) {
-    if player_query.is_empty() {
-        return;
+    for (entity, mut transform, mut knockback) in enemy_query.iter_mut() {
+        transform.translation += knockback.0.extend(0.0) * time.delta_seconds();
+        knockback.0 *= (-KNOCKBACK_DECAY * time.delta_seconds()).exp();
+        if knockback.0.length_squared() < 1.0 {
+            commands.entity(entity).remove::<Knockback>();
+        }
     }
-    let player_translation = player_query.single().translation.truncate();
-
-    for mut enemy in &mut enemy_query {
-        let enemy_translation = enemy.translation.truncate();
-        let direction = (player_translation - enemy_translation).normalize()
-            * ENEMY_SPEED
-            * time.delta_seconds();
-        enemy.translation += vec3(direction.x, direction.y, 0.0);
+}
+
+fn despawn_dead_enemies(
+    mut commands: Commands,
+    // Bosses play their own death sequence before despawning
+    enemy_query: Query<(&Enemy, Entity), Without<Boss>>,
+    mut run_stats: ResMut<RunStats>,
+    mut player_xp: ResMut<PlayerExperience>,
+) {
+    for (enemy, entity) in enemy_query.iter() {
+        if enemy.health <= 0.0 {
+            // Dropping `Enemy` takes the corpse out of targeting while the death effect plays
+            commands
+                .entity(entity)
+                .remove::<Enemy>()
+                .insert(DeathEffect(Timer::from_seconds(
+                    DEATH_EFFECT_DURATION,
+                    TimerMode::Once,
+                )));
+            run_stats.kills += 1;
+            player_xp.add(enemy.xp);
+        }
     }
 }
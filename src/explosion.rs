@@ -0,0 +1,123 @@
+use crate::{
+    boss::Boss,
+    camera::CameraTrauma,
+    collision::EnemyKdTree,
+    constants::*,
+    damage::Damage,
+    enemy::{Enemy, EnemyDamageEvent, Knockback},
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::prelude::*;
+
+#[derive(Clone, Copy)]
+pub struct Explosion {
+    pub radius: f32,
+    /// Damage at the centre, falling off towards `EXPLOSION_EDGE_FALLOFF` of it at the edge.
+    pub damage: Damage,
+    /// Speed enemies at the centre are pushed away with.
+    pub knockback: f32,
+    pub trauma: f32,
+}
+
+/// Detonates an explosion at `position`. Only the player's weapons explode, so every enemy in
+/// range is hit.
+#[derive(Event)]
+pub struct ExplosionEvent {
+    pub position: Vec2,
+    pub explosion: Explosion,
+}
+
+/// Expanding ring drawn where an explosion went off.
+#[derive(Component)]
+struct ExplosionEffect {
+    timer: Timer,
+    center: Vec2,
+    radius: f32,
+    color: Color,
+}
+
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExplosionEvent>().add_systems(
+            Update,
+            (handle_explosions, draw_explosion_effects).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Share of an explosion's strength felt at `distance` from its centre.
+fn falloff(distance: f32, radius: f32) -> f32 {
+    let t = (distance / radius).clamp(0.0, 1.0);
+    1.0 - t * (1.0 - EXPLOSION_EDGE_FALLOFF)
+}
+
+fn handle_explosions(
+    mut commands: Commands,
+    mut events: EventReader<ExplosionEvent>,
+    tree: Res<EnemyKdTree>,
+    // Bosses are too heavy to be pushed around
+    mut knockback_query: Query<Option<&mut Knockback>, (With<Enemy>, Without<Boss>)>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+    mut trauma: ResMut<CameraTrauma>,
+) {
+    for ExplosionEvent {
+        position,
+        explosion,
+    } in events.read()
+    {
+        trauma.add(explosion.trauma);
+        commands.spawn((
+            ExplosionEffect {
+                timer: Timer::from_seconds(EXPLOSION_EFFECT_DURATION, TimerMode::Once),
+                center: *position,
+                radius: explosion.radius,
+                color: explosion.damage.damage_type.color(),
+            },
+            GameEntity,
+        ));
+
+        for enemy in tree.within_radius(*position, explosion.radius) {
+            let distance = enemy.distance_to(*position);
+            let (amount, crit) = explosion.damage.roll();
+            ew.send(EnemyDamageEvent {
+                entity: enemy.entity,
+                amount: amount * falloff(distance, explosion.radius),
+                damage_type: explosion.damage.damage_type,
+                crit,
+            });
+
+            let offset = enemy.pos - *position;
+            let push = offset.normalize_or(Vec2::X)
+                * explosion.knockback
+                * falloff(distance, explosion.radius);
+            match knockback_query.get_mut(enemy.entity) {
+                Ok(Some(mut knockback)) => knockback.0 += push,
+                Ok(None) => {
+                    commands.entity(enemy.entity).insert(Knockback(push));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+fn draw_explosion_effects(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut effect_query: Query<(Entity, &mut ExplosionEffect)>,
+) {
+    for (entity, mut effect) in effect_query.iter_mut() {
+        if effect.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = effect.timer.fraction();
+        let color = effect.color.with_alpha(1.0 - progress);
+        gizmos.circle_2d(effect.center, effect.radius * progress, color);
+        gizmos.circle_2d(effect.center, effect.radius * progress * 0.7, color);
+    }
+}
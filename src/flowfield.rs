@@ -0,0 +1,587 @@
+use crate::{constants::*, obstacle::Obstacle, player::Player, state::GameState};
+use bevy::prelude::*;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+const UNREACHABLE: u32 = u32::MAX;
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+fn step_cost(step: IVec2) -> u32 {
+    if step.x == 0 || step.y == 0 {
+        STRAIGHT_COST
+    } else {
+        DIAGONAL_COST
+    }
+}
+
+/// `cell` and its eight neighbours.
+fn around(cell: IVec2) -> impl Iterator<Item = IVec2> {
+    std::iter::once(cell).chain(NEIGHBOURS.into_iter().map(move |step| cell + step))
+}
+
+/// Inclusive rectangle of world cells, stored row by row from `min`.
+#[derive(Clone, Copy)]
+struct Region {
+    min: IVec2,
+    max: IVec2,
+}
+
+impl Region {
+    fn centred(center: IVec2, size: i32) -> Self {
+        let min = center - IVec2::splat(size / 2);
+        Self {
+            min,
+            max: min + IVec2::splat(size - 1),
+        }
+    }
+
+    fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(self.min).all() && cell.cmple(self.max).all()
+    }
+
+    fn index(&self, cell: IVec2) -> usize {
+        let local = cell - self.min;
+        (local.y * (self.max.x - self.min.x + 1) + local.x) as usize
+    }
+
+    fn len(&self) -> usize {
+        let size = self.max - self.min + IVec2::ONE;
+        (size.x * size.y) as usize
+    }
+
+    fn cells(self) -> impl Iterator<Item = IVec2> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+/// Search towards the current target over the cells around it.
+struct LocalField {
+    region: Region,
+    costs: Vec<u32>,
+    steps: Vec<IVec2>,
+}
+
+/// Grid of headings towards the player, centred near the player's cell. Every enemy samples the
+/// same field, so steering around obstacles costs one lookup per agent.
+///
+/// The whole window is searched from an anchor cell, and obstacles added or removed since are
+/// patched into that search one cell at a time. Crossing into a new cell only searches again
+/// within `FLOW_FIELD_REPAIR_RADIUS` of the player. Cells further out keep leading to the
+/// anchor, which lands agents inside the repaired region. The window is moved and searched in
+/// full once the player drifts half the repair radius away from the anchor.
+#[derive(Resource)]
+pub struct FlowField {
+    window: Region,
+    /// World cell the field currently leads to.
+    target: Option<IVec2>,
+    /// World cell the whole window was last searched from.
+    anchor: Option<IVec2>,
+    /// Cells covered by each obstacle, so removing one only touches its own cells.
+    footprints: HashMap<Entity, Region>,
+    /// Number of obstacles covering each world cell.
+    obstacle_cells: HashMap<IVec2, u32>,
+    /// Cells covered or uncovered since the last update.
+    changed_cells: Vec<IVec2>,
+    blocked: Vec<bool>,
+    /// Path cost from each cell to the anchor.
+    costs: Vec<u32>,
+    /// Step from each cell to its cheapest neighbour under `costs`.
+    steps: Vec<IVec2>,
+    /// Overrides `steps` wherever the search around the target reaches.
+    local: Option<LocalField>,
+}
+
+impl Default for FlowField {
+    fn default() -> Self {
+        Self::new(FLOW_FIELD_SIZE)
+    }
+}
+
+impl FlowField {
+    /// Field over a window of `size` by `size` cells.
+    pub fn new(size: i32) -> Self {
+        let window = Region::centred(IVec2::ZERO, size);
+        Self {
+            window,
+            target: None,
+            anchor: None,
+            footprints: HashMap::new(),
+            obstacle_cells: HashMap::new(),
+            changed_cells: Vec::new(),
+            blocked: vec![false; window.len()],
+            costs: vec![UNREACHABLE; window.len()],
+            steps: vec![IVec2::ZERO; window.len()],
+            local: None,
+        }
+    }
+
+    pub fn cell_of(position: Vec2) -> IVec2 {
+        (position / FLOW_FIELD_CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        self.window.contains(cell).then(|| self.window.index(cell))
+    }
+
+    fn is_open(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|i| !self.blocked[i])
+    }
+
+    fn heading(&self, cell: IVec2) -> Option<Vec2> {
+        let index = self.index(cell)?;
+        let step = match &self.local {
+            Some(local)
+                if local.region.contains(cell)
+                    && local.costs[local.region.index(cell)] != UNREACHABLE =>
+            {
+                local.steps[local.region.index(cell)]
+            }
+            _ => self.steps[index],
+        };
+        (step != IVec2::ZERO).then(|| step.as_vec2().normalize())
+    }
+
+    /// Heading towards the player from `position`, if the field covers it and a path exists.
+    pub fn direction(&self, position: Vec2) -> Option<Vec2> {
+        self.heading(Self::cell_of(position))
+    }
+
+    /// Whether stepping from `cell` by `step` stays clear and inside `region`, without cutting
+    /// obstacle corners.
+    fn can_step(&self, cell: IVec2, step: IVec2, region: Region) -> bool {
+        let open = |cell: IVec2| region.contains(cell) && self.is_open(cell);
+        open(cell + step)
+            && (step.x == 0
+                || step.y == 0
+                || (open(cell + IVec2::new(step.x, 0)) && open(cell + IVec2::new(0, step.y))))
+    }
+
+    /// Starts blocking the cells under an obstacle, or moves them if it was already tracked.
+    pub fn add_obstacle(&mut self, entity: Entity, center: Vec2, half_size: Vec2) {
+        // Pad obstacles by half a cell so agents steer clear of their edges
+        let padding = Vec2::splat(FLOW_FIELD_CELL_SIZE * 0.5);
+        let footprint = Region {
+            min: Self::cell_of(center - half_size - padding),
+            max: Self::cell_of(center + half_size + padding),
+        };
+        if let Some(old) = self.footprints.insert(entity, footprint) {
+            self.uncover(old);
+        }
+        for cell in footprint.cells() {
+            let count = self.obstacle_cells.entry(cell).or_insert(0);
+            *count += 1;
+            if *count == 1 {
+                self.changed_cells.push(cell);
+            }
+        }
+    }
+
+    pub fn remove_obstacle(&mut self, entity: Entity) {
+        if let Some(footprint) = self.footprints.remove(&entity) {
+            self.uncover(footprint);
+        }
+    }
+
+    fn uncover(&mut self, footprint: Region) {
+        for cell in footprint.cells() {
+            let Some(count) = self.obstacle_cells.get_mut(&cell) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                self.obstacle_cells.remove(&cell);
+                self.changed_cells.push(cell);
+            }
+        }
+    }
+
+    /// Relaxes outwards from `queue` over `region`, lowering `costs` wherever a cheaper path is
+    /// found. Returns the cells that were lowered.
+    fn propagate(
+        &self,
+        costs: &mut [u32],
+        region: Region,
+        mut queue: BinaryHeap<Reverse<(u32, i32, i32)>>,
+    ) -> Vec<IVec2> {
+        let mut lowered = Vec::new();
+        while let Some(Reverse((cost, x, y))) = queue.pop() {
+            let cell = IVec2::new(x, y);
+            if cost > costs[region.index(cell)] {
+                continue;
+            }
+            for step in NEIGHBOURS {
+                if !self.can_step(cell, step, region) {
+                    continue;
+                }
+                let next = cell + step;
+                let next_cost = cost + step_cost(step);
+                let next_index = region.index(next);
+                if next_cost < costs[next_index] {
+                    costs[next_index] = next_cost;
+                    queue.push(Reverse((next_cost, next.x, next.y)));
+                    lowered.push(next);
+                }
+            }
+        }
+        lowered
+    }
+
+    /// Dijkstra outwards from `target` over the cells in `region`.
+    fn search(&self, target: IVec2, region: Region) -> Vec<u32> {
+        let mut costs = vec![UNREACHABLE; region.len()];
+        if region.contains(target) {
+            costs[region.index(target)] = 0;
+            let queue = BinaryHeap::from([Reverse((0, target.x, target.y))]);
+            self.propagate(&mut costs, region, queue);
+        }
+        costs
+    }
+
+    /// Step from `cell` to its cheapest neighbour under `costs`, or zero at the target and in
+    /// cells no path reaches.
+    fn step_towards(&self, costs: &[u32], cell: IVec2, region: Region) -> IVec2 {
+        let cost = costs[region.index(cell)];
+        if cost == UNREACHABLE || cost == 0 {
+            return IVec2::ZERO;
+        }
+        NEIGHBOURS
+            .into_iter()
+            .filter(|&step| self.can_step(cell, step, region))
+            .min_by_key(|&step| costs[region.index(cell + step)])
+            .unwrap_or(IVec2::ZERO)
+    }
+
+    /// Cheapest cost of reaching open `cell` from a neighbour that leads to the anchor.
+    fn cheapest_arrival(&self, cell: IVec2) -> Option<u32> {
+        if !self.is_open(cell) {
+            return None;
+        }
+        NEIGHBOURS
+            .into_iter()
+            .filter_map(|step| {
+                let from = cell + step;
+                let cost = self.costs[self.index(from)?];
+                (cost != UNREACHABLE && self.can_step(from, -step, self.window))
+                    .then(|| cost + step_cost(step))
+            })
+            .min()
+    }
+
+    /// Patches the anchor search after `closed` cells were blocked and `opened` cells cleared,
+    /// ending with the same costs a full search would give.
+    fn repair(&mut self, anchor: IVec2, closed: &[IVec2], opened: &[IVec2]) {
+        // Paths can only get longer for cells left without a neighbour that still leads to the
+        // anchor at their old cost, and for every cell that relied on one of those
+        let mut invalid = HashSet::new();
+        let mut pending: Vec<IVec2> = closed.iter().flat_map(|&cell| around(cell)).collect();
+        while let Some(cell) = pending.pop() {
+            let Some(index) = self.index(cell) else {
+                continue;
+            };
+            let cost = self.costs[index];
+            if cell == anchor || cost == UNREACHABLE || invalid.contains(&cell) {
+                continue;
+            }
+            let supported = self.is_open(cell)
+                && NEIGHBOURS.into_iter().any(|step| {
+                    let from = cell + step;
+                    !invalid.contains(&from)
+                        && self.can_step(from, -step, self.window)
+                        && self.index(from).is_some_and(|i| {
+                            self.costs[i] != UNREACHABLE && self.costs[i] + step_cost(step) == cost
+                        })
+                });
+            if !supported {
+                invalid.insert(cell);
+                pending.extend(around(cell));
+            }
+        }
+        for &cell in &invalid {
+            let index = self.index(cell).unwrap();
+            self.costs[index] = UNREACHABLE;
+        }
+
+        // Search again from the edge of what is still valid, and from every cleared cell
+        let mut queue = BinaryHeap::new();
+        let seeds: Vec<IVec2> = invalid
+            .iter()
+            .copied()
+            .chain(opened.iter().flat_map(|&cell| around(cell)))
+            .collect();
+        for &cell in &seeds {
+            let Some(cost) = self.cheapest_arrival(cell) else {
+                continue;
+            };
+            let index = self.index(cell).unwrap();
+            if cost < self.costs[index] {
+                self.costs[index] = cost;
+                queue.push(Reverse((cost, cell.x, cell.y)));
+            }
+        }
+        let mut costs = std::mem::take(&mut self.costs);
+        let lowered = self.propagate(&mut costs, self.window, queue);
+        self.costs = costs;
+
+        // Headings depend on the costs and blocked cells next to them
+        let changed: HashSet<IVec2> = invalid
+            .into_iter()
+            .chain(seeds)
+            .chain(lowered)
+            .chain(closed.iter().copied())
+            .chain(opened.iter().copied())
+            .flat_map(around)
+            .filter(|&cell| self.window.contains(cell))
+            .collect();
+        for cell in changed {
+            let step = self.step_towards(&self.costs, cell, self.window);
+            let index = self.window.index(cell);
+            self.steps[index] = step;
+        }
+    }
+
+    /// Brings the blocked cells up to date with the obstacles added and removed since the last
+    /// update. Returns whether any cell in the window changed.
+    fn apply_obstacle_changes(&mut self) -> bool {
+        let mut closed = Vec::new();
+        let mut opened = Vec::new();
+        for cell in std::mem::take(&mut self.changed_cells) {
+            let Some(index) = self.index(cell) else {
+                continue;
+            };
+            let blocked = self.obstacle_cells.contains_key(&cell);
+            if self.blocked[index] != blocked {
+                self.blocked[index] = blocked;
+                if blocked {
+                    closed.push(cell);
+                } else {
+                    opened.push(cell);
+                }
+            }
+        }
+        if closed.is_empty() && opened.is_empty() {
+            return false;
+        }
+        if let Some(anchor) = self.anchor {
+            self.repair(anchor, &closed, &opened);
+        }
+        true
+    }
+
+    /// Centres the window on `anchor` and searches all of it from there.
+    fn search_window(&mut self, anchor: IVec2) {
+        let size = self.window.max.x - self.window.min.x + 1;
+        self.window = Region::centred(anchor, size);
+        self.blocked = self
+            .window
+            .cells()
+            .map(|cell| self.obstacle_cells.contains_key(&cell))
+            .collect();
+        self.anchor = Some(anchor);
+        self.costs = self.search(anchor, self.window);
+        self.steps = self
+            .window
+            .cells()
+            .map(|cell| self.step_towards(&self.costs, cell, self.window))
+            .collect();
+    }
+
+    /// Points the field at `target`, searching the whole window only when the player left the
+    /// repair radius of the anchor.
+    fn update(&mut self, target: IVec2) {
+        let drifted = !self.anchor.is_some_and(|anchor| {
+            (target - anchor).abs().max_element() <= FLOW_FIELD_REPAIR_RADIUS / 2
+        });
+        if drifted {
+            self.changed_cells.clear();
+            self.search_window(target);
+            self.local = None;
+            self.target = Some(target);
+            return;
+        }
+
+        let obstacles_changed = self.apply_obstacle_changes();
+        if obstacles_changed || self.target != Some(target) {
+            self.local = (self.anchor != Some(target)).then(|| {
+                let reach = IVec2::splat(FLOW_FIELD_REPAIR_RADIUS);
+                let region = Region {
+                    min: (target - reach).max(self.window.min),
+                    max: (target + reach).min(self.window.max),
+                };
+                let costs = self.search(target, region);
+                let steps = region
+                    .cells()
+                    .map(|cell| self.step_towards(&costs, cell, region))
+                    .collect();
+                LocalField {
+                    region,
+                    costs,
+                    steps,
+                }
+            });
+        }
+        self.target = Some(target);
+    }
+}
+
+#[derive(Resource, Default)]
+struct FlowFieldDebug(bool);
+
+pub struct FlowFieldPlugin;
+
+impl Plugin for FlowFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .init_resource::<FlowFieldDebug>()
+            .add_systems(OnEnter(GameState::GameInit), reset_flow_field)
+            .add_systems(
+                Update,
+                (
+                    track_obstacles,
+                    update_flow_field,
+                    toggle_flow_field_debug,
+                    draw_flow_field_debug,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn reset_flow_field(mut flow_field: ResMut<FlowField>) {
+    *flow_field = FlowField::default();
+}
+
+fn track_obstacles(
+    mut flow_field: ResMut<FlowField>,
+    changed_query: Query<(Entity, &Transform, &Obstacle), Changed<Obstacle>>,
+    mut removed: RemovedComponents<Obstacle>,
+) {
+    for entity in removed.read() {
+        flow_field.remove_obstacle(entity);
+    }
+    for (entity, transform, obstacle) in changed_query.iter() {
+        flow_field.add_obstacle(entity, transform.translation.truncate(), obstacle.half_size);
+    }
+}
+
+/// Only updates when the player crosses into a new cell or the obstacles change.
+fn update_flow_field(
+    mut flow_field: ResMut<FlowField>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let target = FlowField::cell_of(player_transform.translation.truncate());
+    if !flow_field.changed_cells.is_empty() || flow_field.target != Some(target) {
+        flow_field.update(target);
+    }
+}
+
+fn toggle_flow_field_debug(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut debug: ResMut<FlowFieldDebug>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        debug.0 = !debug.0;
+    }
+}
+
+fn draw_flow_field_debug(
+    mut gizmos: Gizmos,
+    debug: Res<FlowFieldDebug>,
+    flow_field: Res<FlowField>,
+) {
+    let Some(target) = flow_field.target.filter(|_| debug.0) else {
+        return;
+    };
+
+    let radius = FLOW_FIELD_DEBUG_RADIUS;
+    let cell_size = Vec2::splat(FLOW_FIELD_CELL_SIZE);
+    for y in -radius..=radius {
+        for x in -radius..=radius {
+            let cell = target + IVec2::new(x, y);
+            let center = (cell.as_vec2() + 0.5) * FLOW_FIELD_CELL_SIZE;
+            if !flow_field.is_open(cell) {
+                if flow_field.index(cell).is_some() {
+                    gizmos.rect_2d(center, 0.0, cell_size, Color::srgba(1.0, 0.2, 0.2, 0.6));
+                }
+                continue;
+            }
+            if let Some(direction) = flow_field.heading(cell) {
+                let half = direction * FLOW_FIELD_CELL_SIZE * 0.35;
+                gizmos
+                    .arrow_2d(
+                        center - half,
+                        center + half,
+                        Color::srgba(0.3, 1.0, 0.6, 0.6),
+                    )
+                    .with_tip_length(FLOW_FIELD_CELL_SIZE * 0.2);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell_center(x: f32, y: f32) -> Vec2 {
+        Vec2::new(x, y) * FLOW_FIELD_CELL_SIZE
+    }
+
+    #[test]
+    fn incremental_update_matches_full_search() {
+        let wall = (
+            Entity::from_raw(1),
+            cell_center(3.0, -2.0),
+            cell_center(0.2, 4.0),
+        );
+        let ledge = (
+            Entity::from_raw(2),
+            cell_center(-2.0, 3.0),
+            cell_center(3.0, 0.2),
+        );
+        let gate = (
+            Entity::from_raw(3),
+            cell_center(3.0, 4.5),
+            cell_center(0.2, 2.0),
+        );
+        let add = |field: &mut FlowField, (entity, center, half_size): (Entity, Vec2, Vec2)| {
+            field.add_obstacle(entity, center, half_size);
+        };
+
+        let mut incremental = FlowField::new(16);
+        add(&mut incremental, wall);
+        add(&mut incremental, ledge);
+        incremental.update(IVec2::ZERO);
+        add(&mut incremental, gate);
+        incremental.remove_obstacle(ledge.0);
+        incremental.update(IVec2::ZERO);
+
+        let mut full = FlowField::new(16);
+        add(&mut full, wall);
+        add(&mut full, gate);
+        full.update(IVec2::ZERO);
+
+        assert!(incremental.blocked.iter().any(|&blocked| blocked));
+        assert_eq!(incremental.blocked, full.blocked);
+        assert_eq!(incremental.costs, full.costs);
+        assert_eq!(incremental.steps, full.steps);
+    }
+}
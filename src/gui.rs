@@ -1,17 +1,84 @@
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
 
+use crate::boss::Boss;
+use crate::constants::{MAP_FILES, MAX_WEAPONS};
 use crate::enemy::Enemy;
-use crate::resources::PlayerHealth;
+use crate::gun::{Ammo, Gun, GunCooldown, Reloading, Weapon, WeaponInventory};
+use crate::map::{MapDefinition, MapList, WorldSelection};
+use crate::modifier::ProjectileModifiers;
+use crate::passive::{PassiveKind, PassiveWeapon};
+use crate::resources::{GameResourceSpriteAtlas, PlayerExperience, PlayerHealth, RunStats};
 use crate::state::GameState;
 use crate::world::GameEntity;
 
+#[derive(Component)]
+struct DebugOverlay;
+
 #[derive(Component)]
 struct DebugText;
 
+#[derive(Component)]
+struct HealthBarFill;
+
+#[derive(Component)]
+struct HealthText;
+
+#[derive(Component)]
+struct XpBarFill;
+
+#[derive(Component)]
+struct XpText;
+
+#[derive(Component)]
+struct RunTimerText;
+
+#[derive(Component)]
+struct KillCountText;
+
+#[derive(Component)]
+struct WaveText;
+
+#[derive(Component)]
+struct WeaponText;
+
+/// Magazine and reserve of the weapon in hand, empty for weapons with unlimited ammo.
+#[derive(Component)]
+struct AmmoText;
+
+/// Inventory slot in the weapon bar, highlighted when its weapon is in hand.
+#[derive(Component)]
+struct WeaponSlot(usize);
+
+#[derive(Component)]
+struct WeaponSlotIcon(usize);
+
+#[derive(Component)]
+struct WeaponSlotCooldownFill(usize);
+
+/// Passive weapons the player has picked up and their levels.
+#[derive(Component)]
+struct PassiveText;
+
+#[derive(Component)]
+struct BossBar;
+
+#[derive(Component)]
+struct BossBarFill;
+
+#[derive(Component)]
+struct BossNameText;
+
 #[derive(Component)]
 struct MainMenuItem;
 
+#[derive(Component)]
+struct PlayButton;
+
+/// Cycles through the generated world modes and the hand-authored maps.
+#[derive(Component)]
+struct WorldModeButton;
+
 pub struct GuiPlugin;
 
 impl Plugin for GuiPlugin {
@@ -23,24 +90,79 @@ impl Plugin for GuiPlugin {
                 Update,
                 handle_main_menu_buttons.run_if(in_state(GameState::MainMenu)),
             )
-            .add_systems(OnEnter(GameState::GameInit), spawn_debug_text)
+            .add_systems(
+                OnEnter(GameState::GameInit),
+                (spawn_hud, spawn_boss_bar, spawn_debug_overlay),
+            )
             .add_systems(
                 Update,
-                update_debug_text.run_if(in_state(GameState::InGame)),
+                (
+                    update_health_bar,
+                    update_xp_bar,
+                    update_run_info,
+                    update_weapon_info,
+                    update_passive_info,
+                    update_boss_bar,
+                    toggle_debug_overlay,
+                    update_debug_text,
+                )
+                    .run_if(in_state(GameState::InGame)),
             );
     }
 }
 
-fn spawn_debug_text(mut commands: Commands) {
+fn hud_text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: Color::WHITE,
+        ..default()
+    }
+}
+
+/// Spawns a bar with a background and a fill node tagged with `marker`.
+fn spawn_bar(
+    parent: &mut ChildBuilder,
+    width: f32,
+    height: f32,
+    color: Color,
+    marker: impl Component,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(height),
+                ..default()
+            },
+            background_color: BackgroundColor::from(Color::BLACK.with_alpha(0.7)),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor::from(color),
+                    ..default()
+                },
+                marker,
+            ));
+        });
+}
+
+fn spawn_hud(mut commands: Commands, game_resource: Res<GameResourceSpriteAtlas>) {
     commands
         .spawn((
             NodeBundle {
                 style: Style {
                     width: Val::Percent(100.0),
                     height: Val::Percent(100.0),
-                    align_items: AlignItems::Start,
-                    justify_content: JustifyContent::Start,
                     flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(10.0)),
                     ..default()
                 },
                 ..default()
@@ -48,104 +170,520 @@ fn spawn_debug_text(mut commands: Commands) {
             GameEntity,
         ))
         .with_children(|parent| {
+            // Top row: health and xp on the left, timer and wave in the middle, kills on the right
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Px(345.0),
-                        height: Val::Px(125.0),
-                        align_items: AlignItems::Center,
-                        flex_direction: FlexDirection::Column,
-                        justify_content: JustifyContent::Center,
-                        padding: UiRect::all(Val::Px(8.0)),
-                        margin: UiRect::px(10.0, 10.0, 10.0, 0.0),
+                        width: Val::Percent(100.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        align_items: AlignItems::Start,
                         ..default()
                     },
-                    background_color: BackgroundColor::from(Color::BLACK.with_alpha(0.9)),
                     ..default()
                 })
                 .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(4.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            spawn_bar(
+                                parent,
+                                300.0,
+                                24.0,
+                                Color::srgb(0.8, 0.1, 0.1),
+                                HealthBarFill,
+                            );
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(20.0)),
+                                HealthText,
+                            ));
+                            spawn_bar(parent, 300.0, 10.0, Color::srgb(0.2, 0.6, 1.0), XpBarFill);
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(20.0)),
+                                XpText,
+                            ));
+                        });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(40.0)),
+                                RunTimerText,
+                            ));
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(24.0)),
+                                WaveText,
+                            ));
+                        });
+
                     parent.spawn((
-                        TextBundle::from_section(
-                            "8^D",
-                            TextStyle {
-                                //font: asset_server.load("monogram.ttf"),
-                                font_size: 40.0,
-                                color: Color::WHITE,
+                        TextBundle::from_section("", hud_text_style(30.0)),
+                        KillCountText,
+                    ));
+                });
+
+            // Bottom row: current weapon name above one slot per inventory weapon
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(12.0),
+                                align_items: AlignItems::End,
                                 ..default()
                             },
-                        ),
-                        DebugText,
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(24.0)),
+                                WeaponText,
+                            ));
+                            parent.spawn((
+                                TextBundle::from_section("", hud_text_style(20.0)),
+                                AmmoText,
+                            ));
+                        });
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(6.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for slot in 0..MAX_WEAPONS {
+                                spawn_weapon_slot(parent, &game_resource, slot);
+                            }
+                        });
+                    parent.spawn((
+                        TextBundle::from_section("", hud_text_style(18.0)),
+                        PassiveText,
                     ));
                 });
         });
 }
 
+fn spawn_weapon_slot(
+    parent: &mut ChildBuilder,
+    game_resource: &GameResourceSpriteAtlas,
+    slot: usize,
+) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_alpha(0.5)),
+                border_color: BorderColor(Color::NONE),
+                ..default()
+            },
+            WeaponSlot(slot),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                (slot + 1).to_string(),
+                hud_text_style(14.0),
+            ));
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(48.0),
+                        height: Val::Px(48.0),
+                        ..default()
+                    },
+                    image: UiImage::new(game_resource.sprite_sheet.clone().unwrap()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: game_resource.atlas_layout.clone().unwrap(),
+                    index: 0,
+                },
+                WeaponSlotIcon(slot),
+            ));
+            spawn_bar(
+                parent,
+                48.0,
+                6.0,
+                Color::srgb(0.9, 0.8, 0.2),
+                WeaponSlotCooldownFill(slot),
+            );
+        });
+}
+
+fn spawn_boss_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    bottom: Val::Px(40.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BossBar,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", hud_text_style(28.0)),
+                BossNameText,
+            ));
+            spawn_bar(parent, 600.0, 20.0, Color::srgb(0.6, 0.0, 0.3), BossBarFill);
+        });
+}
+
+fn spawn_debug_overlay(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::BLACK.with_alpha(0.9)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DebugOverlay,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", hud_text_style(20.0)),
+                DebugText,
+            ));
+        });
+}
+
+fn update_health_bar(
+    player_health: Res<PlayerHealth>,
+    mut fill_query: Query<&mut Style, With<HealthBarFill>>,
+    mut text_query: Query<&mut Text, With<HealthText>>,
+) {
+    if fill_query.is_empty() || text_query.is_empty() {
+        return;
+    }
+
+    let fraction = (player_health.value / player_health.max).clamp(0.0, 1.0);
+    fill_query.single_mut().width = Val::Percent(fraction * 100.0);
+    text_query.single_mut().sections[0].value = format!(
+        "{:.0} / {:.0}",
+        player_health.value.max(0.0),
+        player_health.max
+    );
+}
+
+fn update_xp_bar(
+    player_xp: Res<PlayerExperience>,
+    mut fill_query: Query<&mut Style, With<XpBarFill>>,
+    mut text_query: Query<&mut Text, With<XpText>>,
+) {
+    if fill_query.is_empty() || text_query.is_empty() {
+        return;
+    }
+
+    let fraction = (player_xp.value / player_xp.next_level_xp()).clamp(0.0, 1.0);
+    fill_query.single_mut().width = Val::Percent(fraction * 100.0);
+    text_query.single_mut().sections[0].value = format!("Level {}", player_xp.level + 1);
+}
+
+#[allow(clippy::type_complexity)]
+fn update_run_info(
+    run_stats: Res<RunStats>,
+    mut timer_query: Query<
+        &mut Text,
+        (
+            With<RunTimerText>,
+            Without<WaveText>,
+            Without<KillCountText>,
+        ),
+    >,
+    mut wave_query: Query<&mut Text, (With<WaveText>, Without<KillCountText>)>,
+    mut kill_query: Query<&mut Text, With<KillCountText>>,
+) {
+    if timer_query.is_empty() || wave_query.is_empty() || kill_query.is_empty() {
+        return;
+    }
+
+    let elapsed = run_stats.elapsed as u32;
+    timer_query.single_mut().sections[0].value = format!("{:02}:{:02}", elapsed / 60, elapsed % 60);
+    wave_query.single_mut().sections[0].value = format!("Wave {}", run_stats.wave);
+    kill_query.single_mut().sections[0].value = format!("Kills: {}", run_stats.kills);
+}
+
+#[allow(clippy::type_complexity)]
+fn update_weapon_info(
+    inventory: Res<WeaponInventory>,
+    gun_query: Query<
+        (
+            &Name,
+            &GunCooldown,
+            &Weapon,
+            Option<&Ammo>,
+            Option<&Reloading>,
+            Option<&ProjectileModifiers>,
+        ),
+        With<Gun>,
+    >,
+    mut text_query: Query<&mut Text, (With<WeaponText>, Without<AmmoText>)>,
+    mut ammo_text_query: Query<&mut Text, With<AmmoText>>,
+    mut slot_query: Query<(&WeaponSlot, &mut BorderColor)>,
+    mut icon_query: Query<(&WeaponSlotIcon, &mut TextureAtlas, &mut Visibility)>,
+    mut fill_query: Query<(&WeaponSlotCooldownFill, &mut Style)>,
+) {
+    let weapon = |slot: usize| {
+        inventory
+            .slots
+            .get(slot)
+            .and_then(|&entity| gun_query.get(entity).ok())
+    };
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.sections[0].value = weapon(inventory.active)
+            .map(
+                |(name, .., modifiers)| match modifiers.map(ProjectileModifiers::label) {
+                    Some(label) if !label.is_empty() => format!("{name}  {label}"),
+                    _ => name.to_string(),
+                },
+            )
+            .unwrap_or_default();
+    }
+    if let Ok(mut text) = ammo_text_query.get_single_mut() {
+        text.sections[0].value = match weapon(inventory.active) {
+            Some((_, _, _, _, Some(_), _)) => "Reloading".to_string(),
+            Some((_, _, _, Some(ammo), None, _)) => format!("{} / {}", ammo.magazine, ammo.reserve),
+            _ => String::new(),
+        };
+    }
+    for (slot, mut border) in slot_query.iter_mut() {
+        border.0 = if slot.0 == inventory.active && weapon(slot.0).is_some() {
+            Color::WHITE
+        } else {
+            Color::NONE
+        };
+    }
+    for (icon, mut atlas, mut visibility) in icon_query.iter_mut() {
+        match weapon(icon.0) {
+            Some((_, _, weapon, ..)) => {
+                atlas.index = weapon.kind.icon_sprite_index();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    // The bar under a slot tracks the reload while one is in progress
+    for (fill, mut style) in fill_query.iter_mut() {
+        let fraction = weapon(fill.0).map_or(0.0, |(_, cooldown, _, _, reloading, _)| {
+            reloading.map_or(cooldown.0.fraction(), |reloading| reloading.0.fraction())
+        });
+        style.width = Val::Percent(fraction * 100.0);
+    }
+}
+
+fn update_passive_info(
+    passive_query: Query<&PassiveWeapon>,
+    mut text_query: Query<&mut Text, With<PassiveText>>,
+) {
+    let Ok(mut text) = text_query.get_single_mut() else {
+        return;
+    };
+
+    let mut passives: Vec<&PassiveWeapon> = passive_query.iter().collect();
+    passives.sort_by_key(|passive| PassiveKind::ALL.iter().position(|k| *k == passive.kind));
+    text.sections[0].value = passives
+        .iter()
+        .map(|passive| format!("{} Lv {}", passive.kind.name(), passive.level))
+        .collect::<Vec<_>>()
+        .join("  ");
+}
+
+fn update_boss_bar(
+    boss_query: Query<(&Name, Option<&Enemy>), With<Boss>>,
+    mut bar_query: Query<&mut Visibility, With<BossBar>>,
+    mut fill_query: Query<&mut Style, With<BossBarFill>>,
+    mut text_query: Query<&mut Text, With<BossNameText>>,
+) {
+    if bar_query.is_empty() || fill_query.is_empty() || text_query.is_empty() {
+        return;
+    }
+
+    let mut visibility = bar_query.single_mut();
+    let Some((name, enemy)) = boss_query.iter().next() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    // A boss without `Enemy` is playing its death sequence
+    let fraction = enemy.map_or(0.0, |enemy| {
+        (enemy.health / enemy.max_health).clamp(0.0, 1.0)
+    });
+    *visibility = Visibility::Visible;
+    fill_query.single_mut().width = Val::Percent(fraction * 100.0);
+    text_query.single_mut().sections[0].value = name.to_string();
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay_query: Query<&mut Visibility, With<DebugOverlay>>,
+) {
+    if overlay_query.is_empty() || !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    let mut visibility = overlay_query.single_mut();
+    *visibility = match *visibility {
+        Visibility::Hidden => Visibility::Visible,
+        _ => Visibility::Hidden,
+    };
+}
+
 fn update_debug_text(
     mut query: Query<&mut Text, With<DebugText>>,
     diagnostics: Res<DiagnosticsStore>,
     enemy_query: Query<(), With<Enemy>>,
-    player_health: Res<PlayerHealth>,
 ) {
-    if query.is_empty() || enemy_query.is_empty() {
+    if query.is_empty() {
         return;
     }
 
     let num_enemies = enemy_query.iter().count();
-    let player_health = player_health.value;
-    let mut text = query.single_mut();
-    if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
-        if let Some(value) = fps.smoothed() {
-            text.sections[0].value =
-                format!("Fps: {value:.2}\nEnemies: {num_enemies}\nHealth: {player_health}");
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    query.single_mut().sections[0].value = format!("Fps: {fps:.2}\nEnemies: {num_enemies}");
+}
+
+fn world_selection_label(
+    selection: &WorldSelection,
+    map_list: &MapList,
+    maps: &Assets<MapDefinition>,
+) -> String {
+    match selection {
+        WorldSelection::Bounded => "World: Bounded".to_string(),
+        WorldSelection::Infinite => "World: Infinite".to_string(),
+        WorldSelection::Map(i) => {
+            let name = selection
+                .map(map_list, maps)
+                .map_or_else(|| MAP_FILES[*i].to_string(), |map| map.name.clone());
+            format!("Map: {name}")
         }
     }
 }
 
-fn setup_main_menu(mut commands: Commands) {
+fn spawn_menu_button(parent: &mut ChildBuilder, width: f32, label: &str, marker: impl Component) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    height: Val::Px(65.0),
+                    border: UiRect::all(Val::Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(Color::BLACK),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::BLACK,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn setup_main_menu(
+    mut commands: Commands,
+    world_selection: Res<WorldSelection>,
+    map_list: Res<MapList>,
+    maps: Res<Assets<MapDefinition>>,
+) {
     commands
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center,
+                row_gap: Val::Px(15.0),
                 ..default()
             },
             ..default()
         })
         .with_children(|parent| {
-            parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        width: Val::Px(150.0),
-                        height: Val::Px(65.0),
-                        border: UiRect::all(Val::Px(5.0)),
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        ..default()
-                    },
-                    border_color: BorderColor(Color::BLACK),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font_size: 40.0,
-                            color: Color::BLACK,
-                            ..default()
-                        },
-                    ));
-                });
+            spawn_menu_button(parent, 150.0, "Play", PlayButton);
+            spawn_menu_button(
+                parent,
+                360.0,
+                &world_selection_label(&world_selection, &map_list, &maps),
+                WorldModeButton,
+            );
         })
         .insert(MainMenuItem);
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_main_menu_buttons(
-    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    play_query: Query<&Interaction, (Changed<Interaction>, With<PlayButton>)>,
+    world_mode_query: Query<
+        (&Interaction, &Children),
+        (Changed<Interaction>, With<WorldModeButton>),
+    >,
+    mut text_query: Query<&mut Text>,
+    mut world_selection: ResMut<WorldSelection>,
+    map_list: Res<MapList>,
+    maps: Res<Assets<MapDefinition>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for interaction in interaction_query.iter() {
+    for interaction in play_query.iter() {
         match interaction {
             Interaction::Pressed => {
                 next_state.set(GameState::GameInit);
@@ -153,6 +691,18 @@ fn handle_main_menu_buttons(
             _ => {}
         }
     }
+
+    for (interaction, children) in world_mode_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        *world_selection = world_selection.next(map_list.0.len());
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = world_selection_label(&world_selection, &map_list, &maps);
+            }
+        }
+    }
 }
 
 fn despawn_main_menu(mut commands: Commands, menu_items_query: Query<Entity, With<MainMenuItem>>) {
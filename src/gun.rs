@@ -1,11 +1,25 @@
 use crate::{
-    constants::*, out_of_bounds, player::Player, resources::*, state::*, world::GameEntity,
+    camera::CameraTrauma,
+    collision::CollisionLayer,
+    constants::*,
+    damage::{Damage, DamageType},
+    explosion::{Explosion, ExplosionEvent},
+    hitscan::HitscanEvent,
+    melee::MeleeSwing,
+    modifier::{split_bullet, ProjectileModifiers},
+    player::Player,
+    resources::*,
+    state::*,
+    status::{OnHitStatus, StatusEffect},
+    world::GameEntity,
 };
 use bevy::{
+    input::mouse::MouseWheel,
     math::{vec2, vec3},
     prelude::*,
+    utils::HashMap,
 };
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 
 #[derive(Component)]
 pub struct GunCooldown(pub Timer);
@@ -13,113 +27,875 @@ pub struct GunCooldown(pub Timer);
 #[derive(Component)]
 pub struct Gun;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WeaponKind {
+    Shotgun,
+    Rifle,
+    Smg,
+    FrostCannon,
+    Railgun,
+    Laser,
+    RocketLauncher,
+    GrenadeLauncher,
+}
+
+/// How a weapon delivers its damage.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FireMode {
+    /// Spawns `Bullet`s that travel at `speed`.
+    Projectile { speed: f32 },
+    /// Hits the first enemy along a ray instantly.
+    Hitscan { range: f32 },
+    /// Hits everything along a ray of `length` every time the gun cools down, for as long as the
+    /// trigger is held.
+    Beam { length: f32 },
+}
+
+/// Everything needed to spawn a weapon of a given kind.
+pub struct WeaponDefinition {
+    pub name: &'static str,
+    pub fire_rate: f32,
+    pub bullets: u32,
+    /// Total angle the bullets of one shot are fanned across.
+    pub spread: f32,
+    pub fire_mode: FireMode,
+    pub damage: Damage,
+    pub on_hit: Option<OnHitStatus>,
+    /// Magazine and reserve limits. `None` never runs out of ammo.
+    pub ammo: Option<AmmoDefinition>,
+    /// Turns projectiles into rockets or grenades.
+    pub explosive: Option<Explosive>,
+    /// Tint applied to the gun sprite held in hand.
+    pub tint: Color,
+}
+
+/// Explosion carried by a weapon's projectiles.
+#[derive(Clone, Copy)]
+pub struct Explosive {
+    pub explosion: Explosion,
+    /// Detonate on hitting an enemy or obstacle, otherwise only when the fuse runs out.
+    pub on_impact: bool,
+    /// Seconds the projectile flies before it detonates on its own.
+    pub fuse: f32,
+}
+
+pub struct AmmoDefinition {
+    pub magazine_size: u32,
+    pub reload_time: f32,
+    pub max_reserve: u32,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 8] = [
+        WeaponKind::Shotgun,
+        WeaponKind::Rifle,
+        WeaponKind::Smg,
+        WeaponKind::FrostCannon,
+        WeaponKind::Railgun,
+        WeaponKind::Laser,
+        WeaponKind::RocketLauncher,
+        WeaponKind::GrenadeLauncher,
+    ];
+
+    pub fn definition(&self) -> WeaponDefinition {
+        match self {
+            WeaponKind::Shotgun => WeaponDefinition {
+                name: "Shotgun",
+                fire_rate: GUN_FIRE_RATE,
+                bullets: NUM_BULLETS,
+                spread: BULLET_RADIUS,
+                fire_mode: FireMode::Projectile {
+                    speed: BULLET_SPEED,
+                },
+                damage: Damage {
+                    amount: BULLET_DAMAGE,
+                    damage_type: DamageType::Physical,
+                    crit_chance: SHOTGUN_CRIT_CHANCE,
+                    crit_multiplier: SHOTGUN_CRIT_MULTIPLIER,
+                },
+                on_hit: Some(OnHitStatus {
+                    effect: StatusEffect::burn(),
+                    chance: SHOTGUN_BURN_CHANCE,
+                }),
+                ammo: None,
+                explosive: None,
+                tint: Color::WHITE,
+            },
+            WeaponKind::Rifle => WeaponDefinition {
+                name: "Rifle",
+                fire_rate: RIFLE_FIRE_RATE,
+                bullets: 1,
+                spread: 0.0,
+                fire_mode: FireMode::Projectile {
+                    speed: RIFLE_BULLET_SPEED,
+                },
+                damage: Damage {
+                    amount: RIFLE_DAMAGE,
+                    damage_type: DamageType::Physical,
+                    crit_chance: RIFLE_CRIT_CHANCE,
+                    crit_multiplier: RIFLE_CRIT_MULTIPLIER,
+                },
+                on_hit: None,
+                ammo: Some(AmmoDefinition {
+                    magazine_size: RIFLE_MAGAZINE_SIZE,
+                    reload_time: RIFLE_RELOAD_TIME,
+                    max_reserve: RIFLE_MAX_RESERVE,
+                }),
+                explosive: None,
+                tint: Color::srgb(0.7, 0.8, 1.0),
+            },
+            WeaponKind::Smg => WeaponDefinition {
+                name: "SMG",
+                fire_rate: SMG_FIRE_RATE,
+                bullets: 2,
+                spread: SMG_SPREAD,
+                fire_mode: FireMode::Projectile {
+                    speed: BULLET_SPEED,
+                },
+                damage: Damage::new(SMG_DAMAGE, DamageType::Poison),
+                on_hit: Some(OnHitStatus {
+                    effect: StatusEffect::poison(),
+                    chance: SMG_POISON_CHANCE,
+                }),
+                ammo: Some(AmmoDefinition {
+                    magazine_size: SMG_MAGAZINE_SIZE,
+                    reload_time: SMG_RELOAD_TIME,
+                    max_reserve: SMG_MAX_RESERVE,
+                }),
+                explosive: None,
+                tint: Color::srgb(0.5, 1.0, 0.5),
+            },
+            WeaponKind::FrostCannon => WeaponDefinition {
+                name: "Frost Cannon",
+                fire_rate: FROST_CANNON_FIRE_RATE,
+                bullets: FROST_CANNON_BULLETS,
+                spread: FROST_CANNON_SPREAD,
+                fire_mode: FireMode::Projectile {
+                    speed: FROST_CANNON_BULLET_SPEED,
+                },
+                damage: Damage::new(FROST_CANNON_DAMAGE, DamageType::Ice),
+                on_hit: Some(OnHitStatus {
+                    effect: StatusEffect::freeze(),
+                    chance: FROST_CANNON_FREEZE_CHANCE,
+                }),
+                ammo: Some(AmmoDefinition {
+                    magazine_size: FROST_CANNON_MAGAZINE_SIZE,
+                    reload_time: FROST_CANNON_RELOAD_TIME,
+                    max_reserve: FROST_CANNON_MAX_RESERVE,
+                }),
+                explosive: None,
+                tint: Color::srgb(0.4, 0.8, 1.0),
+            },
+            WeaponKind::Railgun => WeaponDefinition {
+                name: "Railgun",
+                fire_rate: RAILGUN_FIRE_RATE,
+                bullets: 1,
+                spread: 0.0,
+                fire_mode: FireMode::Hitscan {
+                    range: RAILGUN_RANGE,
+                },
+                damage: Damage {
+                    amount: RAILGUN_DAMAGE,
+                    damage_type: DamageType::Physical,
+                    crit_chance: RAILGUN_CRIT_CHANCE,
+                    crit_multiplier: RAILGUN_CRIT_MULTIPLIER,
+                },
+                on_hit: None,
+                ammo: Some(AmmoDefinition {
+                    magazine_size: RAILGUN_MAGAZINE_SIZE,
+                    reload_time: RAILGUN_RELOAD_TIME,
+                    max_reserve: RAILGUN_MAX_RESERVE,
+                }),
+                explosive: None,
+                tint: Color::srgb(0.6, 0.6, 1.0),
+            },
+            WeaponKind::Laser => WeaponDefinition {
+                name: "Laser",
+                fire_rate: LASER_TICK_RATE,
+                bullets: 1,
+                spread: 0.0,
+                fire_mode: FireMode::Beam {
+                    length: LASER_LENGTH,
+                },
+                damage: Damage::new(LASER_DPS * LASER_TICK_RATE, DamageType::Fire),
+                on_hit: Some(OnHitStatus {
+                    effect: StatusEffect::burn(),
+                    chance: LASER_BURN_CHANCE,
+                }),
+                ammo: Some(AmmoDefinition {
+                    magazine_size: LASER_MAGAZINE_SIZE,
+                    reload_time: LASER_RELOAD_TIME,
+                    max_reserve: LASER_MAX_RESERVE,
+                }),
+                explosive: None,
+                tint: Color::srgb(1.0, 0.5, 0.4),
+            },
+            WeaponKind::RocketLauncher => WeaponDefinition {
+                name: "Rocket Launcher",
+                fire_rate: ROCKET_FIRE_RATE,
+                bullets: 1,
+                spread: 0.0,
+                fire_mode: FireMode::Projectile {
+                    speed: ROCKET_SPEED,
+                },
+                damage: Damage::new(ROCKET_DAMAGE, DamageType::Physical),
+                on_hit: None,
+                ammo: Some(AmmoDefinition {
+                    magazine_size: ROCKET_MAGAZINE_SIZE,
+                    reload_time: ROCKET_RELOAD_TIME,
+                    max_reserve: ROCKET_MAX_RESERVE,
+                }),
+                explosive: Some(Explosive {
+                    explosion: Explosion {
+                        radius: ROCKET_EXPLOSION_RADIUS,
+                        damage: Damage::new(ROCKET_EXPLOSION_DAMAGE, DamageType::Fire),
+                        knockback: ROCKET_KNOCKBACK,
+                        trauma: ROCKET_TRAUMA,
+                    },
+                    on_impact: true,
+                    fuse: BULLET_LIFETIME,
+                }),
+                tint: Color::srgb(1.0, 0.6, 0.6),
+            },
+            WeaponKind::GrenadeLauncher => WeaponDefinition {
+                name: "Grenade Launcher",
+                fire_rate: GRENADE_FIRE_RATE,
+                bullets: 1,
+                spread: 0.0,
+                fire_mode: FireMode::Projectile {
+                    speed: GRENADE_SPEED,
+                },
+                damage: Damage::new(GRENADE_DAMAGE, DamageType::Physical),
+                on_hit: None,
+                ammo: Some(AmmoDefinition {
+                    magazine_size: GRENADE_MAGAZINE_SIZE,
+                    reload_time: GRENADE_RELOAD_TIME,
+                    max_reserve: GRENADE_MAX_RESERVE,
+                }),
+                explosive: Some(Explosive {
+                    explosion: Explosion {
+                        radius: GRENADE_EXPLOSION_RADIUS,
+                        damage: Damage::new(GRENADE_EXPLOSION_DAMAGE, DamageType::Physical),
+                        knockback: GRENADE_KNOCKBACK,
+                        trauma: GRENADE_TRAUMA,
+                    },
+                    on_impact: false,
+                    fuse: GRENADE_FUSE,
+                }),
+                tint: Color::srgb(0.6, 0.9, 0.5),
+            },
+        }
+    }
+
+    /// Index into the resource sheet of this weapon's icon, shown in the HUD and on its pickup.
+    pub fn icon_sprite_index(&self) -> usize {
+        match self {
+            WeaponKind::Shotgun => 6,
+            WeaponKind::Rifle => 7,
+            WeaponKind::Smg => 8,
+            WeaponKind::FrostCannon => 9,
+            WeaponKind::Railgun => 10,
+            WeaponKind::Laser => 11,
+            WeaponKind::RocketLauncher => 12,
+            WeaponKind::GrenadeLauncher => 13,
+        }
+    }
+}
+
+/// How a gun fans out its shots. Damage and on-hit effects live in their own components.
+#[derive(Component)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub bullets: u32,
+    pub spread: f32,
+    pub fire_mode: FireMode,
+    pub explosive: Option<Explosive>,
+}
+
+/// Rounds left in the magazine and in reserve. Guns without it have unlimited ammo.
+#[derive(Component)]
+pub struct Ammo {
+    pub magazine: u32,
+    pub magazine_size: u32,
+    pub reserve: u32,
+    pub max_reserve: u32,
+    pub reload_time: f32,
+}
+
+impl Ammo {
+    fn new(definition: &AmmoDefinition) -> Self {
+        Self {
+            magazine: definition.magazine_size,
+            magazine_size: definition.magazine_size,
+            reserve: definition.max_reserve,
+            max_reserve: definition.max_reserve,
+            reload_time: definition.reload_time,
+        }
+    }
+
+    pub fn can_reload(&self) -> bool {
+        self.magazine < self.magazine_size && self.reserve > 0
+    }
+
+    /// Moves rounds from the reserve into the magazine.
+    fn reload(&mut self) {
+        let loaded = (self.magazine_size - self.magazine).min(self.reserve);
+        self.magazine += loaded;
+        self.reserve -= loaded;
+    }
+
+    pub fn add_reserve(&mut self, amount: u32) {
+        self.reserve = (self.reserve + amount).min(self.max_reserve);
+    }
+}
+
+/// Present while a gun is reloading. Only the gun in hand reloads, switching away cancels it.
+#[derive(Component)]
+pub struct Reloading(pub Timer);
+
+/// The guns the player carries, in slot order. Only the active one is shown and fires.
+#[derive(Resource, Default)]
+pub struct WeaponInventory {
+    pub slots: Vec<Entity>,
+    pub active: usize,
+}
+
+impl WeaponInventory {
+    pub fn active_weapon(&self) -> Option<Entity> {
+        self.slots.get(self.active).copied()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.len() >= MAX_WEAPONS
+    }
+
+    /// Switches to `slot` if it holds a weapon.
+    pub fn select(&mut self, slot: usize) {
+        if slot < self.slots.len() {
+            self.active = slot;
+        }
+    }
+
+    /// Adds a weapon and switches to it. When full, the active weapon is replaced and returned.
+    pub fn add(&mut self, weapon: Entity) -> Option<Entity> {
+        if self.is_full() {
+            return Some(std::mem::replace(&mut self.slots[self.active], weapon));
+        }
+        self.slots.push(weapon);
+        self.active = self.slots.len() - 1;
+        None
+    }
+}
+
+pub fn spawn_weapon(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    kind: WeaponKind,
+) -> Entity {
+    let definition = kind.definition();
+    let mut weapon = commands.spawn((
+        SpriteBundle {
+            texture: game_resource.sprite_sheet.clone().unwrap(),
+            sprite: Sprite {
+                color: definition.tint,
+                ..default()
+            },
+            transform: Transform::from_translation(vec3(0.0, 0.0, 3.0))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        TextureAtlas {
+            layout: game_resource.atlas_layout.clone().unwrap(),
+            index: 0,
+        },
+        Gun,
+        Weapon {
+            kind,
+            bullets: definition.bullets,
+            spread: definition.spread,
+            fire_mode: definition.fire_mode,
+            explosive: definition.explosive,
+        },
+        Name::new(definition.name),
+        GunDamage(definition.damage),
+        GunCooldown(Timer::from_seconds(definition.fire_rate, TimerMode::Once)),
+        ProjectileModifiers::default(),
+        GameEntity,
+    ));
+    if let Some(on_hit) = definition.on_hit {
+        weapon.insert(GunOnHitStatus(on_hit));
+    }
+    if let Some(ammo) = &definition.ammo {
+        weapon.insert(Ammo::new(ammo));
+    }
+    weapon.id()
+}
+
+/// Damage, type and crit stats of each bullet the gun fires.
+#[derive(Component)]
+pub struct GunDamage(pub Damage);
+
+/// Status effect the gun's bullets may inflict on enemies they hit.
+#[derive(Component)]
+pub struct GunOnHitStatus(pub OnHitStatus);
+
 #[derive(Component)]
 pub struct Bullet {
-    velocity: Vec3,
-    lifetime: Timer,
+    pub velocity: Vec3,
+    pub lifetime: Timer,
+    pub layer: CollisionLayer,
+    pub damage: Damage,
+    pub on_hit: Option<OnHitStatus>,
+    /// Where the bullet was before its last move, so collisions can sweep the path between.
+    pub previous_position: Vec2,
+    /// Detonates when the bullet hits an enemy or an obstacle, ending its flight.
+    pub on_impact: Option<Explosion>,
+    /// Detonates when the bullet's lifetime runs out or it leaves the world.
+    pub on_expire: Option<Explosion>,
+    pub modifiers: ProjectileModifiers,
+}
+
+impl Bullet {
+    pub fn new(direction: Vec2, speed: f32, layer: CollisionLayer, damage: Damage) -> Self {
+        Self {
+            velocity: direction.normalize().extend(0.0) * speed,
+            lifetime: Timer::from_seconds(BULLET_LIFETIME, TimerMode::Once),
+            layer,
+            damage,
+            on_hit: None,
+            previous_position: Vec2::ZERO,
+            on_impact: None,
+            on_expire: None,
+            modifiers: ProjectileModifiers::default(),
+        }
+    }
+}
+
+/// Why a bullet stopped flying.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BulletStop {
+    /// Hit an enemy, which also splits splitting bullets.
+    HitEnemy,
+    /// Hit the player or an obstacle.
+    Impact,
+    /// Its lifetime ran out or it left the world.
+    Expired,
+}
+
+/// Ends a bullet's flight. Sent by whichever system stops the bullet and resolved once all of them
+/// have run, so a bullet stopped by several things in one frame still only detonates once.
+#[derive(Event)]
+pub struct BulletStopEvent {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub reason: BulletStop,
 }
 
 pub struct GunPlugin;
 
 impl Plugin for GunPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (update_gun_transform, update_bullets, handle_gun_input)
-                .run_if(in_state(GameState::InGame)),
-        );
+        app.init_resource::<WeaponInventory>()
+            .add_event::<BulletStopEvent>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_weapon_switch,
+                        update_weapon_visibility.run_if(resource_changed::<WeaponInventory>),
+                    )
+                        .chain(),
+                    update_gun_transform,
+                    update_bullets,
+                    (handle_reload_input, update_reloads, handle_gun_input).chain(),
+                    draw_reload_ring,
+                )
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                PostUpdate,
+                resolve_bullet_stops.run_if(in_state(GameState::InGame)),
+            );
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn update_gun_transform(
     cursor_pos: Res<CursorPos>,
-    player_query: Query<&Transform, With<Player>>,
-    mut gun_query: Query<&mut Transform, (With<Gun>, Without<Player>)>,
+    player_query: Query<(&Transform, Option<&MeleeSwing>), With<Player>>,
+    mut gun_query: Query<(&mut Transform, Option<&Reloading>), (With<Gun>, Without<Player>)>,
 ) {
     if player_query.is_empty() || gun_query.is_empty() {
         return;
     }
 
-    let player_position = player_query.single().translation.truncate();
+    let (player_transform, swing) = player_query.single();
+    let player_position = player_transform.translation.truncate();
     let cursor_position = match cursor_pos.0 {
         Some(pos) => pos,
         None => player_position,
     };
-    let mut gun_transform = gun_query.single_mut();
 
     let angle = (cursor_position.y - player_position.y)
         .atan2(cursor_position.x - player_position.x)
         - (PI / 2.0);
-    gun_transform.rotation = Quat::from_rotation_z(angle);
-
+    // Melee swings sweep the gun around the player across the arc
+    let angle = angle + swing.map_or(0.0, |swing| swing.offset());
     let offset = 50.0;
     let new_gun_pos = vec2(
         player_position.x + offset * angle.sin() * -1.0,
         player_position.y + offset * angle.cos(),
     );
-    gun_transform.translation = vec3(new_gun_pos.x, new_gun_pos.y, gun_transform.translation.z);
+
+    // Holstered guns follow along too so they are already in place when switched to
+    for (mut gun_transform, reloading) in gun_query.iter_mut() {
+        // Reloading guns spin once over the reload
+        let spin = reloading.map_or(0.0, |reloading| reloading.0.fraction() * TAU);
+        gun_transform.rotation = Quat::from_rotation_z(angle + spin);
+        gun_transform.translation = vec3(new_gun_pos.x, new_gun_pos.y, gun_transform.translation.z);
+    }
 }
 
+fn handle_weapon_switch(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mut inventory: ResMut<WeaponInventory>,
+) {
+    const SLOT_KEYS: [KeyCode; MAX_WEAPONS] = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+
+    let scroll: f32 = wheel_events.read().map(|event| event.y).sum();
+    if let Some(slot) = SLOT_KEYS
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+    {
+        inventory.select(slot);
+    } else if scroll != 0.0 && inventory.slots.len() > 1 {
+        let count = inventory.slots.len();
+        let step = if scroll > 0.0 { count - 1 } else { 1 };
+        let slot = (inventory.active + step) % count;
+        inventory.select(slot);
+    }
+}
+
+fn update_weapon_visibility(
+    inventory: Res<WeaponInventory>,
+    mut gun_query: Query<(Entity, &mut Visibility), With<Gun>>,
+) {
+    let active = inventory.active_weapon();
+    for (entity, mut visibility) in gun_query.iter_mut() {
+        *visibility = if Some(entity) == active {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn handle_gun_input(
     mut commands: Commands,
     game_resource: Res<GameResourceSpriteAtlas>,
     //global_sprite: Res<GlobalSpriteTextureHandle>,
-    mut gun_query: Query<(&Transform, &mut GunCooldown), (With<Gun>, Without<Player>)>,
+    mut gun_query: Query<
+        (
+            &Transform,
+            &mut GunCooldown,
+            &Weapon,
+            &GunDamage,
+            Option<&GunOnHitStatus>,
+            &ProjectileModifiers,
+            Option<&mut Ammo>,
+            Has<Reloading>,
+        ),
+        (With<Gun>, Without<Player>),
+    >,
+    inventory: Res<WeaponInventory>,
     time: Res<Time>,
     mouse_input: Res<ButtonInput<MouseButton>>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut hitscan_ew: EventWriter<HitscanEvent>,
 ) {
-    if gun_query.is_empty() {
+    // Every weapon keeps cooling down while holstered
+    for (_, mut gun_timer, ..) in gun_query.iter_mut() {
+        gun_timer.0.tick(time.delta());
+    }
+
+    let Some(Ok((
+        gun_transform,
+        mut gun_timer,
+        weapon,
+        damage,
+        on_hit,
+        modifiers,
+        ammo,
+        reloading,
+    ))) = inventory
+        .active_weapon()
+        .map(|entity| gun_query.get_mut(entity))
+    else {
+        return;
+    };
+    if reloading || ammo.as_ref().is_some_and(|ammo| ammo.magazine == 0) {
         return;
     }
-    let (gun_transform, mut gun_timer) = gun_query.single_mut();
-    gun_timer.0.tick(time.delta());
-    if mouse_input.just_pressed(MouseButton::Left)
-        || (mouse_input.pressed(MouseButton::Left) && gun_timer.0.finished())
-    {
+    // Clicking fires straight away, but a beam must cool down on every tick or mashing the trigger
+    // would skip its cooldown
+    let is_beam = matches!(weapon.fire_mode, FireMode::Beam { .. });
+    let clicked = mouse_input.just_pressed(MouseButton::Left) && !is_beam;
+    if clicked || (mouse_input.pressed(MouseButton::Left) && gun_timer.0.finished()) {
         gun_timer.0.reset();
+        if let Some(mut ammo) = ammo {
+            ammo.magazine -= 1;
+        }
+        // A beam fires every cooldown, shaking on each tick would never stop
+        if !is_beam {
+            trauma.add(GUN_FIRE_TRAUMA);
+        }
         let gun_rotation = gun_transform.rotation.to_euler(EulerRot::XYZ).2 + (PI / 2.0);
-        let radian_step = BULLET_RADIUS / (NUM_BULLETS + 1) as f32;
-        let mut bullet_direction = gun_rotation - (BULLET_RADIUS / 2.0);
+        let radian_step = weapon.spread / (weapon.bullets + 1) as f32;
+        let mut bullet_direction = gun_rotation - (weapon.spread / 2.0);
 
-        for _ in 0..NUM_BULLETS {
+        for _ in 0..weapon.bullets {
             bullet_direction += radian_step;
-            commands.spawn((
-                SpriteBundle {
-                    texture: game_resource.sprite_sheet.clone().unwrap(),
-                    transform: Transform {
-                        translation: gun_transform.translation,
-                        rotation: gun_transform.rotation,
-                        scale: Vec3::splat(SPRITE_SCALE_FACTOR),
-                    },
-                    ..default()
-                },
-                TextureAtlas {
-                    layout: game_resource.atlas_layout.clone().unwrap(),
-                    index: 1,
-                },
-                Bullet {
-                    velocity: vec3(bullet_direction.cos(), bullet_direction.sin(), 0.0).normalize()
-                        * BULLET_SPEED,
-                    lifetime: Timer::from_seconds(2.0, TimerMode::Once),
-                },
-                GameEntity,
-            ));
+            let direction = vec2(bullet_direction.cos(), bullet_direction.sin());
+            let (range, pierce) = match weapon.fire_mode {
+                FireMode::Projectile { speed } => {
+                    let mut bullet = Bullet {
+                        on_hit: on_hit.map(|on_hit| on_hit.0),
+                        modifiers: *modifiers,
+                        ..Bullet::new(direction, speed, CollisionLayer::Player, damage.0)
+                    };
+                    if let Some(explosive) = weapon.explosive {
+                        bullet.lifetime = Timer::from_seconds(explosive.fuse, TimerMode::Once);
+                        bullet.on_impact = explosive.on_impact.then_some(explosive.explosion);
+                        bullet.on_expire = Some(explosive.explosion);
+                    }
+                    spawn_bullet(
+                        &mut commands,
+                        &game_resource,
+                        gun_transform.translation,
+                        bullet,
+                    );
+                    continue;
+                }
+                FireMode::Hitscan { range } => (range, false),
+                FireMode::Beam { length } => (length, true),
+            };
+            hitscan_ew.send(HitscanEvent {
+                origin: gun_transform.translation.truncate(),
+                direction,
+                range,
+                pierce,
+                damage: damage.0,
+                on_hit: on_hit.map(|on_hit| on_hit.0),
+            });
         }
     }
 }
 
-fn update_bullets(
+/// Starts a reload on the reload key, or on its own once the magazine is empty.
+fn handle_reload_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    inventory: Res<WeaponInventory>,
+    gun_query: Query<&Ammo, Without<Reloading>>,
+) {
+    let Some((entity, ammo)) = inventory
+        .active_weapon()
+        .and_then(|entity| gun_query.get(entity).ok().map(|ammo| (entity, ammo)))
+    else {
+        return;
+    };
+
+    if ammo.can_reload() && (ammo.magazine == 0 || keyboard_input.just_pressed(KeyCode::KeyF)) {
+        commands
+            .entity(entity)
+            .insert(Reloading(Timer::from_seconds(
+                ammo.reload_time,
+                TimerMode::Once,
+            )));
+    }
+}
+
+fn update_reloads(
     mut commands: Commands,
     time: Res<Time>,
+    inventory: Res<WeaponInventory>,
+    mut gun_query: Query<(Entity, &mut Reloading, &mut Ammo)>,
+) {
+    for (entity, mut reloading, mut ammo) in gun_query.iter_mut() {
+        if Some(entity) != inventory.active_weapon() {
+            commands.entity(entity).remove::<Reloading>();
+            continue;
+        }
+        if reloading.0.tick(time.delta()).finished() {
+            ammo.reload();
+            commands.entity(entity).remove::<Reloading>();
+        }
+    }
+}
+
+fn draw_reload_ring(mut gizmos: Gizmos, gun_query: Query<(&Transform, &Reloading)>) {
+    for (transform, reloading) in gun_query.iter() {
+        let center = transform.translation.truncate() + Vec2::Y * RELOAD_RING_OFFSET;
+        let fraction = reloading.0.fraction();
+        gizmos.circle_2d(center, RELOAD_RING_RADIUS, Color::BLACK.with_alpha(0.4));
+        gizmos.arc_2d(
+            center,
+            fraction * PI,
+            fraction * TAU,
+            RELOAD_RING_RADIUS,
+            Color::srgb(0.9, 0.8, 0.2),
+        );
+    }
+}
+
+/// Spawns a projectile at `position`, facing along its velocity. Shared by the player's gun and
+/// enemy attacks.
+pub fn spawn_bullet(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    position: Vec3,
+    bullet: Bullet,
+) {
+    let color = match bullet.layer {
+        CollisionLayer::Player => Color::WHITE,
+        CollisionLayer::Enemy => Color::srgb(1.0, 0.3, 0.3),
+    };
+    let direction = bullet.velocity.truncate();
+    commands.spawn((
+        SpriteBundle {
+            texture: game_resource.sprite_sheet.clone().unwrap(),
+            sprite: Sprite { color, ..default() },
+            transform: Transform {
+                translation: position,
+                rotation: Quat::from_rotation_z(direction.y.atan2(direction.x) - (PI / 2.0)),
+                scale: Vec3::splat(SPRITE_SCALE_FACTOR),
+            },
+            ..default()
+        },
+        TextureAtlas {
+            layout: game_resource.atlas_layout.clone().unwrap(),
+            index: 1,
+        },
+        Bullet {
+            previous_position: position.truncate(),
+            ..bullet
+        },
+        GameEntity,
+    ));
+}
+
+/// Mirrors `velocity` on every axis where `position` lies outside the bounds it was clamped to.
+fn reflect_off_bounds(position: Vec2, clamped: Vec2, velocity: Vec2) -> Vec2 {
+    velocity * Vec2::select(position.cmpne(clamped), -Vec2::ONE, Vec2::ONE)
+}
+
+fn update_bullets(
+    time: Res<Time>,
+    world_bounds: Res<WorldBounds>,
     mut bullet_query: Query<(Entity, &mut Transform, &mut Bullet)>,
+    mut stop_ew: EventWriter<BulletStopEvent>,
 ) {
     for (bullet_entity, mut bullet_transform, mut bullet) in &mut bullet_query {
         bullet.lifetime.tick(time.delta());
-        let bullet_pos = bullet_transform.translation;
-        if bullet.lifetime.finished() || out_of_bounds(&bullet_pos, WORLD_W, WORLD_H) {
-            commands.entity(bullet_entity).despawn();
+        let bullet_pos = bullet_transform.translation.truncate();
+        if bullet.modifiers.ricochets > 0 && !world_bounds.contains(bullet_pos) {
+            // Bounce off the edge that was crossed and carry on from just inside it
+            let clamped = world_bounds.clamp(bullet_pos);
+            let velocity = reflect_off_bounds(bullet_pos, clamped, bullet.velocity.truncate());
+            bullet.velocity = velocity.extend(0.0);
+            bullet.modifiers.ricochets -= 1;
+            bullet.previous_position = clamped;
+            bullet_transform.translation = clamped.extend(bullet_transform.translation.z);
+            bullet_transform.rotation = Quat::from_rotation_z(velocity.to_angle() - (PI / 2.0));
+        } else if bullet.lifetime.finished() || !world_bounds.contains(bullet_pos) {
+            stop_ew.send(BulletStopEvent {
+                entity: bullet_entity,
+                position: bullet_pos,
+                reason: BulletStop::Expired,
+            });
         } else {
+            bullet.previous_position = bullet_pos;
             bullet_transform.translation += bullet.velocity * time.delta_seconds();
         }
     }
 }
+
+/// Despawns every stopped bullet, setting off its explosion and splitting it. A bullet that both
+/// hit something and expired this frame counts as a hit, at the first place it hit.
+fn resolve_bullet_stops(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    mut events: EventReader<BulletStopEvent>,
+    bullet_query: Query<(&Transform, &Bullet)>,
+    mut explosion_ew: EventWriter<ExplosionEvent>,
+) {
+    let mut stops: HashMap<Entity, (Vec2, BulletStop)> = HashMap::new();
+    for event in events.read() {
+        let stop = stops
+            .entry(event.entity)
+            .or_insert((event.position, event.reason));
+        if stop.1 == BulletStop::Expired && event.reason != BulletStop::Expired {
+            *stop = (event.position, event.reason);
+        }
+    }
+
+    for (entity, (position, reason)) in stops {
+        let Ok((transform, bullet)) = bullet_query.get(entity) else {
+            continue;
+        };
+        let explosion = match reason {
+            // Fused projectiles stopped before their fuse runs out still go off
+            BulletStop::HitEnemy | BulletStop::Impact => bullet.on_impact.or(bullet.on_expire),
+            BulletStop::Expired => bullet.on_expire,
+        };
+        if reason == BulletStop::HitEnemy && bullet.modifiers.splits > 0 {
+            split_bullet(
+                &mut commands,
+                &game_resource,
+                position.extend(transform.translation.z),
+                bullet,
+            );
+        }
+        if let Some(explosion) = explosion {
+            explosion_ew.send(ExplosionEvent {
+                position,
+                explosion,
+            });
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reflect_off_bounds_flips_only_the_crossed_axis() {
+        let velocity = Vec2::new(3.0, 4.0);
+        let reflected =
+            reflect_off_bounds(Vec2::new(105.0, 50.0), Vec2::new(100.0, 50.0), velocity);
+        assert_eq!(reflected, Vec2::new(-3.0, 4.0));
+
+        let reflected = reflect_off_bounds(Vec2::new(50.0, -5.0), Vec2::new(50.0, 0.0), velocity);
+        assert_eq!(reflected, Vec2::new(3.0, -4.0));
+    }
+
+    #[test]
+    fn reflect_off_bounds_flips_both_axes_in_a_corner() {
+        let reflected = reflect_off_bounds(
+            Vec2::new(105.0, 105.0),
+            Vec2::new(100.0, 100.0),
+            Vec2::new(3.0, 4.0),
+        );
+        assert_eq!(reflected, Vec2::new(-3.0, -4.0));
+    }
+}
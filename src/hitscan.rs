@@ -0,0 +1,153 @@
+use crate::{
+    collision::EnemyKdTree,
+    constants::*,
+    damage::Damage,
+    enemy::EnemyDamageEvent,
+    obstacle::ObstacleGrid,
+    state::GameState,
+    status::{ApplyStatusEvent, OnHitStatus},
+    world::GameEntity,
+};
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// An instant shot along a ray, fired by hitscan and beam weapons. The ray stops at obstacles.
+#[derive(Event)]
+pub struct HitscanEvent {
+    pub origin: Vec2,
+    pub direction: Vec2,
+    pub range: f32,
+    /// Hit everything along the ray instead of stopping at the first enemy.
+    pub pierce: bool,
+    pub damage: Damage,
+    pub on_hit: Option<OnHitStatus>,
+}
+
+/// Line left behind by a ray, fading out over its lifetime.
+#[derive(Component)]
+struct Tracer(Timer);
+
+pub struct HitscanPlugin;
+
+impl Plugin for HitscanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HitscanEvent>().add_systems(
+            Update,
+            (handle_hitscan_events, fade_tracers).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn handle_hitscan_events(
+    mut commands: Commands,
+    mut events: EventReader<HitscanEvent>,
+    tree: Res<EnemyKdTree>,
+    grid: Res<ObstacleGrid>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+    mut status_ew: EventWriter<ApplyStatusEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        let direction = event.direction.normalize_or_zero();
+        if direction == Vec2::ZERO {
+            continue;
+        }
+        let length = grid.raycast(event.origin, direction, event.range);
+
+        // Distance along the ray of a target close enough to it to be hit
+        let along = |position: Vec2, radius: f32| {
+            let distance = (position - event.origin).dot(direction);
+            let closest = event.origin + direction * distance;
+            ((0.0..=length).contains(&distance) && closest.distance(position) < radius)
+                .then_some(distance)
+        };
+
+        let center = event.origin + direction * length / 2.0;
+        let mut hits: Vec<(f32, Entity)> = tree
+            .within_radius(center, length / 2.0 + HITSCAN_HIT_RADIUS)
+            .into_iter()
+            .filter_map(|enemy| {
+                along(enemy.pos, HITSCAN_HIT_RADIUS + enemy.radius).map(|d| (d, enemy.entity))
+            })
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if !event.pierce {
+            hits.truncate(1);
+        }
+
+        // A single shot's tracer ends on the enemy it hit
+        let end = match hits.first() {
+            Some(&(distance, _)) if !event.pierce => distance,
+            _ => length,
+        };
+        let width = if event.pierce {
+            BEAM_WIDTH
+        } else {
+            TRACER_WIDTH
+        };
+        spawn_tracer(
+            &mut commands,
+            event.origin,
+            direction,
+            end,
+            width,
+            event.damage.damage_type.color(),
+        );
+
+        for (_, entity) in hits {
+            let (amount, crit) = event.damage.roll();
+            ew.send(EnemyDamageEvent {
+                entity,
+                amount,
+                damage_type: event.damage.damage_type,
+                crit,
+            });
+            if let Some(on_hit) = event.on_hit.filter(|on_hit| rng.gen_bool(on_hit.chance)) {
+                status_ew.send(ApplyStatusEvent {
+                    entity,
+                    effect: on_hit.effect,
+                });
+            }
+        }
+    }
+}
+
+fn spawn_tracer(
+    commands: &mut Commands,
+    origin: Vec2,
+    direction: Vec2,
+    length: f32,
+    width: f32,
+    color: Color,
+) {
+    let center = origin + direction * length / 2.0;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(width, length)),
+                ..default()
+            },
+            transform: Transform::from_translation(center.extend(2.5))
+                .with_rotation(Quat::from_rotation_z(direction.to_angle() - PI / 2.0)),
+            ..default()
+        },
+        Tracer(Timer::from_seconds(TRACER_LIFETIME, TimerMode::Once)),
+        GameEntity,
+    ));
+}
+
+fn fade_tracers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tracer_query: Query<(Entity, &mut Tracer, &mut Sprite)>,
+) {
+    for (entity, mut tracer, mut sprite) in tracer_query.iter_mut() {
+        if tracer.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        sprite.color.set_alpha(1.0 - tracer.0.fraction());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{
+    camera::MainCamera, collision::EnemyKdTree, constants::*, state::GameState, world::GameEntity,
+};
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+/// Gives the entity its own edge-of-screen arrow while it is outside the viewport.
+#[derive(Component)]
+pub struct OffscreenIndicator {
+    pub color: Color,
+}
+
+#[derive(Component)]
+struct IndicatorLabel;
+
+pub struct IndicatorPlugin;
+
+impl Plugin for IndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameInit), spawn_indicator_labels)
+            .add_systems(
+                Update,
+                draw_offscreen_indicators.run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_indicator_labels(mut commands: Commands) {
+    for _ in 0..INDICATOR_SECTORS * 2 {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            IndicatorLabel,
+            GameEntity,
+        ));
+    }
+}
+
+/// Nearest off-screen target and total count for one angular slice around the camera.
+#[derive(Clone, Copy, Default)]
+struct Sector {
+    count: u32,
+    nearest: Option<(f32, Vec2)>,
+    color: Option<Color>,
+}
+
+impl Sector {
+    fn add(&mut self, offset: Vec2, color: Option<Color>) {
+        self.count += 1;
+        let dist = offset.length_squared();
+        if self.nearest.is_none_or(|(best, _)| dist < best) {
+            self.nearest = Some((dist, offset));
+            self.color = color;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_offscreen_indicators(
+    mut gizmos: Gizmos,
+    tree: Res<EnemyKdTree>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    tracked_query: Query<(&Transform, &OffscreenIndicator)>,
+    mut label_query: Query<
+        (&mut Text, &mut Transform, &mut Visibility),
+        (
+            With<IndicatorLabel>,
+            Without<MainCamera>,
+            Without<OffscreenIndicator>,
+        ),
+    >,
+) {
+    if camera_query.is_empty() {
+        return;
+    }
+
+    let (camera_transform, projection) = camera_query.single();
+    let center = camera_transform.translation.truncate();
+    let half_view = projection.area.half_size();
+    let is_offscreen = |offset: Vec2| offset.x.abs() > half_view.x || offset.y.abs() > half_view.y;
+    let sector_of = |offset: Vec2| {
+        let angle = offset.y.atan2(offset.x).rem_euclid(TAU);
+        ((angle / TAU) * INDICATOR_SECTORS as f32) as usize % INDICATOR_SECTORS
+    };
+
+    let mut tracked = [Sector::default(); INDICATOR_SECTORS];
+    for (transform, indicator) in tracked_query.iter() {
+        let offset = transform.translation.truncate() - center;
+        if is_offscreen(offset) {
+            tracked[sector_of(offset)].add(offset, Some(indicator.color));
+        }
+    }
+
+    let mut crowds = [Sector::default(); INDICATOR_SECTORS];
+    for enemy in tree.iter() {
+        let offset = enemy.pos - center;
+        if is_offscreen(offset) {
+            crowds[sector_of(offset)].add(offset, None);
+        }
+    }
+
+    // Arrows and labels keep a constant on-screen size regardless of zoom
+    let scale = projection.scale;
+    let inset = (half_view - Vec2::splat(INDICATOR_MARGIN * scale)).max(Vec2::ONE);
+    let mut labels = label_query.iter_mut();
+
+    let indicators = tracked
+        .iter()
+        .filter(|sector| sector.count > 0)
+        .map(|sector| (sector, sector.color.unwrap_or(Color::WHITE), 1.0))
+        .chain(
+            crowds
+                .iter()
+                .filter(|sector| sector.count >= INDICATOR_CLUSTER_MIN)
+                .map(|sector| (sector, Color::srgba(1.0, 0.5, 0.1, 0.8), 0.75)),
+        );
+    for (sector, color, size) in indicators {
+        let Some((_, offset)) = sector.nearest else {
+            continue;
+        };
+        let direction = offset.normalize();
+        let t = (inset.x / direction.x.abs()).min(inset.y / direction.y.abs());
+        let tip = center + direction * t;
+        let tail = tip - direction * INDICATOR_ARROW_LENGTH * size * scale;
+        gizmos
+            .arrow_2d(tail, tip, color)
+            .with_tip_length(INDICATOR_ARROW_LENGTH * 0.5 * size * scale);
+
+        if sector.count > 1 {
+            if let Some((mut text, mut transform, mut visibility)) = labels.next() {
+                text.sections[0].value = sector.count.to_string();
+                text.sections[0].style.color = color;
+                let label_pos = tail - direction * 15.0 * scale;
+                transform.translation = label_pos.extend(20.0);
+                transform.scale = Vec3::splat(scale);
+                *visibility = Visibility::Visible;
+            }
+        }
+    }
+
+    for (_, _, mut visibility) in labels {
+        *visibility = Visibility::Hidden;
+    }
+}
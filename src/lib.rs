@@ -1,20 +1,30 @@
 pub mod animations;
+pub mod behavior;
+pub mod boss;
 pub mod camera;
 pub mod collision;
 pub mod constants;
+pub mod damage;
+pub mod effects;
+pub mod elite;
 pub mod enemy;
+pub mod explosion;
+pub mod flowfield;
 pub mod gui;
 pub mod gun;
+pub mod hitscan;
+pub mod indicators;
+pub mod map;
+pub mod melee;
+pub mod minimap;
+pub mod modifier;
+pub mod obstacle;
+pub mod passive;
+pub mod pickup;
 pub mod player;
 pub mod resources;
+pub mod spawn_zone;
 pub mod state;
+pub mod status;
+pub mod terrain;
 pub mod world;
-
-use bevy::prelude::*;
-
-pub fn out_of_bounds(pos: &Vec3, world_width: f32, world_h: f32) -> bool {
-    if pos.x > world_width || pos.x < -world_width || pos.y > world_h || pos.y < -world_h {
-        return true;
-    }
-    return false;
-}
@@ -1,14 +1,31 @@
 use bevy::prelude::*;
 use topdown_shooter::animations::AnimationPlugin;
+use topdown_shooter::behavior::EnemyBehaviorPlugin;
+use topdown_shooter::boss::BossPlugin;
 use topdown_shooter::camera::FollowCameraPlugin;
 use topdown_shooter::collision::CollisionPlugin;
 use topdown_shooter::constants::*;
+use topdown_shooter::effects::EffectsPlugin;
+use topdown_shooter::elite::ElitePlugin;
 use topdown_shooter::enemy::EnemyPlugin;
+use topdown_shooter::explosion::ExplosionPlugin;
+use topdown_shooter::flowfield::FlowFieldPlugin;
 use topdown_shooter::gui::GuiPlugin;
 use topdown_shooter::gun::GunPlugin;
+use topdown_shooter::hitscan::HitscanPlugin;
+use topdown_shooter::indicators::IndicatorPlugin;
+use topdown_shooter::map::MapPlugin;
+use topdown_shooter::melee::MeleePlugin;
+use topdown_shooter::minimap::MinimapPlugin;
+use topdown_shooter::modifier::ModifierPlugin;
+use topdown_shooter::obstacle::ObstaclePlugin;
+use topdown_shooter::passive::PassivePlugin;
+use topdown_shooter::pickup::PickupPlugin;
 use topdown_shooter::player::PlayerPlugin;
 use topdown_shooter::resources::ResourcesPlugin;
 use topdown_shooter::state::GameState;
+use topdown_shooter::status::StatusPlugin;
+use topdown_shooter::terrain::TerrainPlugin;
 use topdown_shooter::world::WorldPlugin;
 
 fn main() {
@@ -33,9 +50,26 @@ fn main() {
         .add_plugins(PlayerPlugin)
         .add_plugins(AnimationPlugin)
         .add_plugins(ResourcesPlugin)
+        .add_plugins(MapPlugin)
         .add_plugins(WorldPlugin)
+        .add_plugins(TerrainPlugin)
         .add_plugins(EnemyPlugin)
+        .add_plugins(FlowFieldPlugin)
+        .add_plugins(EnemyBehaviorPlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(ElitePlugin)
+        .add_plugins(StatusPlugin)
+        .add_plugins(ObstaclePlugin)
+        .add_plugins(PickupPlugin)
         .add_plugins(CollisionPlugin)
+        .add_plugins(HitscanPlugin)
+        .add_plugins(ExplosionPlugin)
+        .add_plugins(ModifierPlugin)
+        .add_plugins(PassivePlugin)
+        .add_plugins(MeleePlugin)
+        .add_plugins(EffectsPlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(IndicatorPlugin)
         .run();
 }
 
@@ -0,0 +1,192 @@
+use crate::{
+    constants::*,
+    obstacle::{spawn_obstacle, ObstacleKind},
+    resources::GameDecorationSpriteAtlas,
+    spawn_zone::SpawnZone,
+    state::GameState,
+    terrain::spawn_decoration,
+    world::GameEntity,
+};
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct MapObstacle {
+    pub kind: ObstacleKind,
+    pub position: Vec2,
+    pub half_size: Vec2,
+}
+
+#[derive(Deserialize)]
+pub struct MapDecoration {
+    pub position: Vec2,
+    /// Index into the decoration sprite sheet.
+    pub index: usize,
+}
+
+#[derive(Deserialize)]
+pub struct MapSpawnZone {
+    pub position: Vec2,
+    pub radius: f32,
+    #[serde(default = "default_spawn_weight")]
+    pub weight: f32,
+    #[serde(default = "default_spawn_distance")]
+    pub min_player_distance: f32,
+    #[serde(default)]
+    pub max_enemies: Option<usize>,
+    #[serde(default = "default_offscreen_only")]
+    pub offscreen_only: bool,
+}
+
+fn default_spawn_weight() -> f32 {
+    1.0
+}
+
+fn default_spawn_distance() -> f32 {
+    SPAWN_MIN_PLAYER_DISTANCE
+}
+
+fn default_offscreen_only() -> bool {
+    true
+}
+
+/// A hand-authored arena, loaded from a `.map.ron` file under `assets/maps`.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct MapDefinition {
+    pub name: String,
+    /// Half the arena's width and height. The arena is centred on the origin.
+    pub half_size: Vec2,
+    pub player_start: Vec2,
+    /// Seed for the ground. A random one is rolled each run when left out.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub obstacles: Vec<MapObstacle>,
+    #[serde(default)]
+    pub decorations: Vec<MapDecoration>,
+    /// Where enemies come from. Enemies spawn around the player when a map has none.
+    #[serde(default)]
+    pub spawn_zones: Vec<MapSpawnZone>,
+}
+
+#[derive(Default)]
+struct MapLoader;
+
+impl AssetLoader for MapLoader {
+    type Asset = MapDefinition;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<MapDefinition, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["map.ron"]
+    }
+}
+
+/// Handles to every map listed in `MAP_FILES`, in menu order.
+#[derive(Resource, Default)]
+pub struct MapList(pub Vec<Handle<MapDefinition>>);
+
+/// What the next run is played on. Chosen in the main menu and read by `init_world`.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WorldSelection {
+    #[default]
+    Bounded,
+    Infinite,
+    /// Index into `MapList`.
+    Map(usize),
+}
+
+impl WorldSelection {
+    /// The selection after this one, wrapping back to `Bounded` after the last map.
+    pub fn next(&self, map_count: usize) -> Self {
+        match self {
+            WorldSelection::Bounded => WorldSelection::Infinite,
+            WorldSelection::Infinite if map_count > 0 => WorldSelection::Map(0),
+            WorldSelection::Map(i) if i + 1 < map_count => WorldSelection::Map(i + 1),
+            _ => WorldSelection::Bounded,
+        }
+    }
+
+    /// The selected map, once it has finished loading.
+    pub fn map<'a>(
+        &self,
+        map_list: &MapList,
+        maps: &'a Assets<MapDefinition>,
+    ) -> Option<&'a MapDefinition> {
+        let WorldSelection::Map(i) = self else {
+            return None;
+        };
+        map_list.0.get(*i).and_then(|handle| maps.get(handle))
+    }
+}
+
+/// Places a map's authored obstacles, decorations and spawn zones. The ground is still generated
+/// by the terrain.
+pub fn spawn_map(
+    commands: &mut Commands,
+    game_decorations: &GameDecorationSpriteAtlas,
+    map: &MapDefinition,
+) {
+    for obstacle in &map.obstacles {
+        spawn_obstacle(
+            commands,
+            obstacle.kind,
+            obstacle.half_size,
+            obstacle.position,
+        );
+    }
+    for decoration in &map.decorations {
+        spawn_decoration(
+            commands,
+            game_decorations,
+            decoration.position,
+            decoration.index,
+        );
+    }
+    for zone in &map.spawn_zones {
+        commands.spawn((
+            Transform::from_translation(zone.position.extend(0.0)),
+            SpawnZone {
+                radius: zone.radius,
+                weight: zone.weight,
+                min_player_distance: zone.min_player_distance,
+                max_enemies: zone.max_enemies,
+                offscreen_only: zone.offscreen_only,
+            },
+            GameEntity,
+        ));
+    }
+}
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MapDefinition>()
+            .init_asset_loader::<MapLoader>()
+            .init_resource::<MapList>()
+            .init_resource::<WorldSelection>()
+            .add_systems(OnEnter(GameState::Loading), load_maps);
+    }
+}
+
+fn load_maps(asset_server: Res<AssetServer>, mut map_list: ResMut<MapList>) {
+    map_list.0 = MAP_FILES
+        .iter()
+        .map(|path| asset_server.load(*path))
+        .collect();
+}
@@ -0,0 +1,149 @@
+use crate::{
+    boss::Boss,
+    camera::CameraTrauma,
+    collision::EnemyKdTree,
+    constants::*,
+    damage::{Damage, DamageType},
+    enemy::{Enemy, EnemyDamageEvent, Knockback},
+    player::Player,
+    resources::CursorPos,
+    state::GameState,
+};
+use bevy::prelude::*;
+use std::f32::consts::PI;
+
+/// Time until the player can swing again.
+#[derive(Component)]
+pub struct MeleeCooldown(pub Timer);
+
+impl Default for MeleeCooldown {
+    /// Starts ready so the first swing of a run is never blocked.
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(MELEE_COOLDOWN, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+}
+
+/// A swing in progress. The gun in hand sweeps across the arc while it lasts.
+#[derive(Component)]
+pub struct MeleeSwing {
+    timer: Timer,
+    /// Angle of the middle of the arc.
+    aim: f32,
+}
+
+impl MeleeSwing {
+    /// Rotation of the gun away from the aim, from one edge of the arc to the other.
+    pub fn offset(&self) -> f32 {
+        -MELEE_ARC / 2.0 + MELEE_ARC * self.timer.fraction()
+    }
+}
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_melee_input, update_melee_swing).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn melee_pressed(
+    mouse_input: &ButtonInput<MouseButton>,
+    gamepads: &Gamepads,
+    gamepad_input: &ButtonInput<GamepadButton>,
+) -> bool {
+    mouse_input.just_pressed(MouseButton::Right)
+        || gamepads.iter().any(|gamepad| {
+            gamepad_input.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West))
+        })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_melee_input(
+    mut commands: Commands,
+    time: Res<Time>,
+    cursor_pos: Res<CursorPos>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    tree: Res<EnemyKdTree>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut player_query: Query<(Entity, &Transform, &mut MeleeCooldown), With<Player>>,
+    // Bosses are too heavy to be pushed around
+    mut knockback_query: Query<Option<&mut Knockback>, (With<Enemy>, Without<Boss>)>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+) {
+    let Ok((player, player_transform, mut cooldown)) = player_query.get_single_mut() else {
+        return;
+    };
+    cooldown.0.tick(time.delta());
+    if !cooldown.0.finished() || !melee_pressed(&mouse_input, &gamepads, &gamepad_input) {
+        return;
+    }
+    cooldown.0.reset();
+    trauma.add(MELEE_TRAUMA);
+
+    let player_pos = player_transform.translation.truncate();
+    let aim = cursor_pos
+        .0
+        .map(|cursor| cursor - player_pos)
+        .filter(|offset| *offset != Vec2::ZERO)
+        .unwrap_or(Vec2::X)
+        .normalize();
+    let damage = Damage::new(MELEE_DAMAGE, DamageType::Physical);
+    for enemy in tree.within_radius(player_pos, MELEE_RANGE) {
+        let offset = enemy.pos - player_pos;
+        if aim.angle_between(offset).abs() > MELEE_ARC / 2.0 {
+            continue;
+        }
+        let (amount, crit) = damage.roll();
+        ew.send(EnemyDamageEvent {
+            entity: enemy.entity,
+            amount,
+            damage_type: damage.damage_type,
+            crit,
+        });
+
+        let push = offset.normalize_or(aim) * MELEE_KNOCKBACK;
+        match knockback_query.get_mut(enemy.entity) {
+            Ok(Some(mut knockback)) => knockback.0 += push,
+            Ok(None) => {
+                commands.entity(enemy.entity).insert(Knockback(push));
+            }
+            Err(_) => {}
+        }
+    }
+
+    commands.entity(player).insert(MeleeSwing {
+        timer: Timer::from_seconds(MELEE_SWING_DURATION, TimerMode::Once),
+        aim: aim.to_angle(),
+    });
+}
+
+fn update_melee_swing(
+    mut commands: Commands,
+    mut gizmos: Gizmos,
+    time: Res<Time>,
+    mut player_query: Query<(Entity, &Transform, &mut MeleeSwing), With<Player>>,
+) {
+    let Ok((player, player_transform, mut swing)) = player_query.get_single_mut() else {
+        return;
+    };
+    if swing.timer.tick(time.delta()).finished() {
+        commands.entity(player).remove::<MeleeSwing>();
+        return;
+    }
+
+    // Trail of the swept arc, fading out as the swing ends
+    gizmos.arc_2d(
+        player_transform.translation.truncate(),
+        PI / 2.0 - swing.aim,
+        MELEE_ARC,
+        MELEE_RANGE,
+        Color::WHITE.with_alpha(0.5 * (1.0 - swing.timer.fraction())),
+    );
+}
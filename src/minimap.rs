@@ -0,0 +1,210 @@
+use crate::{
+    collision::EnemyKdTree, constants::*, player::Player, resources::WorldBounds, state::GameState,
+    world::GameEntity,
+};
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    time::common_conditions::on_timer,
+    utils::Duration,
+};
+
+/// Draws the entity on the minimap as a square of `size` pixels.
+#[derive(Component)]
+pub struct MinimapMarker {
+    pub color: Color,
+    pub size: u32,
+}
+
+#[derive(Component)]
+struct MinimapNode;
+
+#[derive(Resource)]
+pub struct MinimapSettings {
+    pub visible: bool,
+    pub width: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            width: MINIMAP_SIZES[1],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct MinimapImage(Handle<Image>);
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinimapSettings>()
+            .init_resource::<MinimapImage>()
+            .add_systems(OnEnter(GameState::GameInit), spawn_minimap)
+            .add_systems(
+                Update,
+                (
+                    handle_minimap_input,
+                    update_minimap_node,
+                    draw_minimap.run_if(on_timer(Duration::from_secs_f32(MINIMAP_REFRESH_RATE))),
+                )
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn spawn_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut minimap_image: ResMut<MinimapImage>,
+    settings: Res<MinimapSettings>,
+) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: MINIMAP_RESOLUTION.0,
+            height: MINIMAP_RESOLUTION.1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    minimap_image.0 = images.add(image);
+
+    commands.spawn((
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(60.0),
+                right: Val::Px(10.0),
+                width: Val::Px(settings.width),
+                aspect_ratio: Some(WORLD_W / WORLD_H),
+                ..default()
+            },
+            image: UiImage::new(minimap_image.0.clone()),
+            visibility: if settings.visible {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            },
+            ..default()
+        },
+        MinimapNode,
+        GameEntity,
+    ));
+}
+
+fn handle_minimap_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<MinimapSettings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        settings.visible = !settings.visible;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        let next = MINIMAP_SIZES
+            .iter()
+            .position(|&w| w == settings.width)
+            .map_or(0, |i| (i + 1) % MINIMAP_SIZES.len());
+        settings.width = MINIMAP_SIZES[next];
+    }
+}
+
+fn update_minimap_node(
+    settings: Res<MinimapSettings>,
+    mut node_query: Query<(&mut Style, &mut Visibility), With<MinimapNode>>,
+) {
+    if !settings.is_changed() || node_query.is_empty() {
+        return;
+    }
+
+    let (mut style, mut visibility) = node_query.single_mut();
+    style.width = Val::Px(settings.width);
+    *visibility = if settings.visible {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn draw_minimap(
+    settings: Res<MinimapSettings>,
+    minimap_image: Res<MinimapImage>,
+    mut images: ResMut<Assets<Image>>,
+    tree: Res<EnemyKdTree>,
+    world_bounds: Res<WorldBounds>,
+    marker_query: Query<(&Transform, &MinimapMarker)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !settings.visible {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap_image.0) else {
+        return;
+    };
+
+    // An unbounded world has no fixed extent, so show a world-sized window around the player
+    let (center, half) = match world_bounds.half_size {
+        Some(half) => (Vec2::ZERO, half),
+        None => (
+            player_query
+                .get_single()
+                .map_or(Vec2::ZERO, |transform| transform.translation.truncate()),
+            Vec2::new(WORLD_W, WORLD_H),
+        ),
+    };
+    let (w, h) = MINIMAP_RESOLUTION;
+    let to_pixel = |pos: Vec2| -> Option<(u32, u32)> {
+        let offset = pos - center;
+        let u = (offset.x + half.x) / (2.0 * half.x);
+        // Image rows run top to bottom, world y runs bottom to top
+        let v = (half.y - offset.y) / (2.0 * half.y);
+        if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+            return None;
+        }
+        Some(((u * w as f32) as u32, (v * h as f32) as u32))
+    };
+
+    let mut density = vec![0u32; (w * h) as usize];
+    for enemy in tree.iter() {
+        if let Some((x, y)) = to_pixel(enemy.pos) {
+            density[(y * w + x) as usize] += 1;
+        }
+    }
+
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) as usize;
+            let color = if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                [255, 255, 255, 255]
+            } else if density[i] > 0 {
+                let heat = (density[i] as f32 / MINIMAP_DENSITY_SATURATION).min(1.0);
+                [255, (heat * 220.0) as u8, 0, (120.0 + heat * 135.0) as u8]
+            } else {
+                [0, 0, 0, 150]
+            };
+            image.data[i * 4..i * 4 + 4].copy_from_slice(&color);
+        }
+    }
+
+    for (transform, marker) in marker_query.iter() {
+        let Some((cx, cy)) = to_pixel(transform.translation.truncate()) else {
+            continue;
+        };
+        let color = marker.color.to_srgba().to_u8_array();
+        let half = marker.size / 2;
+        for y in cy.saturating_sub(half)..(cy + marker.size - half).min(h) {
+            for x in cx.saturating_sub(half)..(cx + marker.size - half).min(w) {
+                let i = ((y * w + x) * 4) as usize;
+                image.data[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
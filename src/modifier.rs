@@ -0,0 +1,188 @@
+use crate::{
+    collision::{CollisionLayer, EnemyKdTree},
+    constants::*,
+    gun::{spawn_bullet, Bullet, FireMode, Weapon, WeaponInventory},
+    passive::{GrantPassiveEvent, PassiveKind},
+    resources::{GameResourceSpriteAtlas, PlayerExperience},
+    state::GameState,
+};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use std::f32::consts::PI;
+
+/// Stackable projectile behaviours. Each field counts how many times the modifier was stacked.
+/// Weapons carry one and copy it onto every bullet they fire.
+#[derive(Component, Clone, Copy, Default)]
+pub struct ProjectileModifiers {
+    /// Each stack lets the bullet turn faster towards the nearest enemy.
+    pub homing: u32,
+    /// Times the bullet bounces off the world's edge instead of despawning.
+    pub ricochets: u32,
+    /// Generations of child bullets spawned when the bullet hits an enemy.
+    pub splits: u32,
+}
+
+impl ProjectileModifiers {
+    /// Short summary for the HUD, empty when nothing is stacked.
+    pub fn label(&self) -> String {
+        [
+            ("Homing", self.homing),
+            ("Ricochet", self.ricochets),
+            ("Split", self.splits),
+        ]
+        .into_iter()
+        .filter(|(_, stacks)| *stacks > 0)
+        .map(|(name, stacks)| format!("{name} x{stacks}"))
+        .collect::<Vec<_>>()
+        .join("  ")
+    }
+}
+
+/// Reward rolled on level up: a modifier for the weapon in hand, or a passive weapon.
+#[derive(Clone, Copy)]
+pub enum Upgrade {
+    Homing,
+    Ricochet,
+    Split,
+    Passive(PassiveKind),
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 6] = [
+        Upgrade::Homing,
+        Upgrade::Ricochet,
+        Upgrade::Split,
+        Upgrade::Passive(PassiveKind::OrbitBlades),
+        Upgrade::Passive(PassiveKind::Aura),
+        Upgrade::Passive(PassiveKind::AutoTurret),
+    ];
+
+    /// Stacks a modifier upgrade. Passive upgrades leave the modifiers untouched.
+    pub fn apply(&self, modifiers: &mut ProjectileModifiers) {
+        match self {
+            Upgrade::Homing => modifiers.homing += 1,
+            Upgrade::Ricochet => modifiers.ricochets += 1,
+            Upgrade::Split => modifiers.splits += 1,
+            Upgrade::Passive(_) => {}
+        }
+    }
+}
+
+pub struct ModifierPlugin;
+
+impl Plugin for ModifierPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (steer_homing_bullets, grant_level_up_upgrades).run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Spawns the children of a splitting bullet that hit something at `position`. The children fan
+/// out around the parent's heading with one split generation less.
+pub fn split_bullet(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    position: Vec3,
+    bullet: &Bullet,
+) {
+    let heading = bullet.velocity.truncate();
+    let speed = heading.length();
+    let step = SPLIT_SPREAD / (SPLIT_COUNT - 1) as f32;
+    for i in 0..SPLIT_COUNT {
+        let angle = -SPLIT_SPREAD / 2.0 + step * i as f32;
+        let direction = Vec2::from_angle(angle).rotate(heading.normalize_or_zero());
+        let mut damage = bullet.damage;
+        damage.amount *= SPLIT_DAMAGE_FRACTION;
+        let mut modifiers = bullet.modifiers;
+        modifiers.splits -= 1;
+        // Start clear of the enemy that was hit so the child does not split on it straight away
+        let offset = (direction * SPLIT_CLEARANCE).extend(0.0);
+        spawn_bullet(
+            commands,
+            game_resource,
+            position + offset,
+            Bullet {
+                on_hit: bullet.on_hit,
+                modifiers,
+                ..Bullet::new(direction, speed, bullet.layer, damage)
+            },
+        );
+    }
+}
+
+fn steer_homing_bullets(
+    time: Res<Time>,
+    tree: Res<EnemyKdTree>,
+    mut bullet_query: Query<(&mut Transform, &mut Bullet)>,
+) {
+    for (mut transform, mut bullet) in bullet_query.iter_mut() {
+        if bullet.modifiers.homing == 0 || bullet.layer != CollisionLayer::Player {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let Some(nearest) = tree
+            .nearest(position)
+            .filter(|enemy| enemy.distance_to(position) <= HOMING_RANGE)
+        else {
+            continue;
+        };
+
+        // Turn towards the target, but no faster than the stacked turn rate allows
+        let velocity = bullet.velocity.truncate();
+        let max_turn = HOMING_TURN_RATE * bullet.modifiers.homing as f32 * time.delta_seconds();
+        let turn = velocity
+            .angle_between(nearest.pos - position)
+            .clamp(-max_turn, max_turn);
+        let velocity = Vec2::from_angle(turn).rotate(velocity);
+        bullet.velocity = velocity.extend(0.0);
+        transform.rotation = Quat::from_rotation_z(velocity.to_angle() - PI / 2.0);
+    }
+}
+
+/// Every level gained rolls an upgrade, either stacked onto the weapon in hand or granted as a
+/// passive weapon. Modifiers only affect projectiles, so they go to another projectile weapon
+/// when the one in hand fires hitscan or beams, and become a passive when there is none.
+fn grant_level_up_upgrades(
+    player_xp: Res<PlayerExperience>,
+    inventory: Res<WeaponInventory>,
+    mut modifier_query: Query<(&Weapon, &mut ProjectileModifiers)>,
+    mut passive_ew: EventWriter<GrantPassiveEvent>,
+    mut rewarded_level: Local<u32>,
+) {
+    // Experience resets at the start of every run
+    if player_xp.level < *rewarded_level {
+        *rewarded_level = player_xp.level;
+    }
+
+    let mut rng = rand::thread_rng();
+    while *rewarded_level < player_xp.level {
+        *rewarded_level += 1;
+        match Upgrade::ALL.choose(&mut rng) {
+            Some(Upgrade::Passive(kind)) => {
+                passive_ew.send(GrantPassiveEvent(*kind));
+            }
+            Some(upgrade) => {
+                let target = inventory
+                    .active_weapon()
+                    .into_iter()
+                    .chain(inventory.slots.iter().copied())
+                    .find(|entity| {
+                        modifier_query.get(*entity).is_ok_and(|(weapon, _)| {
+                            matches!(weapon.fire_mode, FireMode::Projectile { .. })
+                        })
+                    });
+                match target.and_then(|entity| modifier_query.get_mut(entity).ok()) {
+                    Some((_, mut modifiers)) => upgrade.apply(&mut modifiers),
+                    None => {
+                        if let Some(kind) = PassiveKind::ALL.choose(&mut rng) {
+                            passive_ew.send(GrantPassiveEvent(*kind));
+                        }
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::{
+    constants::*,
+    enemy::Enemy,
+    gun::{Bullet, BulletStop, BulletStopEvent},
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+use rand::Rng;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum ObstacleKind {
+    Rock,
+    Wall,
+    Tree,
+}
+
+impl ObstacleKind {
+    pub fn color(&self) -> Color {
+        match self {
+            ObstacleKind::Rock => Color::srgb(0.45, 0.45, 0.5),
+            ObstacleKind::Wall => Color::srgb(0.4, 0.28, 0.2),
+            ObstacleKind::Tree => Color::srgb(0.15, 0.4, 0.2),
+        }
+    }
+
+    pub fn random_half_size(&self, rng: &mut impl Rng) -> Vec2 {
+        match self {
+            ObstacleKind::Rock => Vec2::splat(rng.gen_range(30.0..70.0)),
+            ObstacleKind::Tree => Vec2::splat(rng.gen_range(25.0..40.0)),
+            ObstacleKind::Wall => {
+                let length = rng.gen_range(200.0..500.0);
+                if rng.gen_bool(0.5) {
+                    Vec2::new(length, 20.0)
+                } else {
+                    Vec2::new(20.0, length)
+                }
+            }
+        }
+    }
+
+    /// Walls bounce bullets back, everything else absorbs them.
+    pub fn ricochets(&self) -> bool {
+        matches!(self, ObstacleKind::Wall)
+    }
+}
+
+/// Static axis-aligned blocker centred on the entity's transform.
+#[derive(Component, Clone, Copy)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub half_size: Vec2,
+}
+
+pub fn spawn_obstacle(
+    commands: &mut Commands,
+    kind: ObstacleKind,
+    half_size: Vec2,
+    position: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(half_size * 2.0),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.extend(1.5)),
+                ..default()
+            },
+            Obstacle { kind, half_size },
+            GameEntity,
+        ))
+        .id()
+}
+
+struct ObstacleCollider {
+    center: Vec2,
+    half_size: Vec2,
+    kind: ObstacleKind,
+}
+
+impl ObstacleCollider {
+    /// Distance along a ray to where it enters the box, with the normal of the face it enters
+    /// through. A ray starting inside enters at zero through the nearest face.
+    fn ray_entry(&self, origin: Vec2, direction: Vec2) -> Option<(f32, Vec2)> {
+        let inverse = direction.recip();
+        let near = (self.center - self.half_size - origin) * inverse;
+        let far = (self.center + self.half_size - origin) * inverse;
+        let entries = near.min(far);
+        let entry = entries.max_element();
+        let exit = near.max(far).min_element();
+        if entry.max(0.0) > exit {
+            return None;
+        }
+        if entry < 0.0 {
+            return Some((0.0, self.exit_normal(origin)));
+        }
+        let normal = if entries.x > entries.y {
+            Vec2::new(-direction.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, -direction.y.signum())
+        };
+        Some((entry, normal))
+    }
+
+    /// Unit normal of the face closest to a point inside the box.
+    fn exit_normal(&self, point: Vec2) -> Vec2 {
+        let offset = point - self.center;
+        let depth = self.half_size - offset.abs();
+        if depth.x < depth.y {
+            Vec2::new(offset.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, offset.y.signum())
+        }
+    }
+}
+
+/// Uniform grid over static obstacles, rebuilt only when obstacles are added or removed.
+#[derive(Resource, Default)]
+pub struct ObstacleGrid {
+    colliders: Vec<ObstacleCollider>,
+    cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl ObstacleGrid {
+    fn cell_of(position: Vec2) -> IVec2 {
+        (position / OBSTACLE_GRID_CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn nearby(&self, min: Vec2, max: Vec2) -> impl Iterator<Item = &ObstacleCollider> + '_ {
+        let (min, max) = (Self::cell_of(min), Self::cell_of(max));
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&i| &self.colliders[i])
+    }
+
+    /// First obstacle a ray meets within `max_distance`, with the distance to it and the
+    /// normal of the face it hits.
+    fn ray_hit(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+    ) -> Option<(&ObstacleCollider, f32, Vec2)> {
+        let end = origin + direction * max_distance;
+        self.nearby(origin.min(end), origin.max(end))
+            .filter_map(|collider| {
+                let (distance, normal) = collider.ray_entry(origin, direction)?;
+                (distance <= max_distance).then_some((collider, distance, normal))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// How far a ray travels before it meets an obstacle, capped at `max_distance`.
+    pub fn raycast(&self, origin: Vec2, direction: Vec2, max_distance: f32) -> f32 {
+        self.ray_hit(origin, direction, max_distance)
+            .map_or(max_distance, |(_, distance, _)| distance)
+    }
+
+    /// Whether a circle at `position` would overlap any obstacle.
+    pub fn blocks(&self, position: Vec2, radius: f32) -> bool {
+        self.nearby(position - radius, position + radius)
+            .any(|collider| {
+                let closest = position.clamp(
+                    collider.center - collider.half_size,
+                    collider.center + collider.half_size,
+                );
+                closest.distance_squared(position) < radius * radius
+            })
+    }
+
+    /// Pushes a circle out of every obstacle it overlaps and returns its new centre.
+    pub fn resolve_circle(&self, position: Vec2, radius: f32) -> Vec2 {
+        let mut position = position;
+        for collider in self.nearby(position - radius, position + radius) {
+            let closest = position.clamp(
+                collider.center - collider.half_size,
+                collider.center + collider.half_size,
+            );
+            let offset = position - closest;
+            let distance = offset.length();
+            if distance >= radius {
+                continue;
+            }
+            position = if distance > 0.0 {
+                closest + offset / distance * radius
+            } else {
+                // Centre is inside the box, leave through the nearest face
+                let normal = collider.exit_normal(position);
+                let face = collider.center + normal * collider.half_size;
+                position * normal.yx().abs() + (face + normal * radius) * normal.abs()
+            };
+        }
+        position
+    }
+
+    fn rebuild(&mut self, obstacles: &Query<(&Transform, &Obstacle)>) {
+        self.colliders.clear();
+        self.cells.clear();
+        for (transform, obstacle) in obstacles.iter() {
+            let center = transform.translation.truncate();
+            let index = self.colliders.len();
+            self.colliders.push(ObstacleCollider {
+                center,
+                half_size: obstacle.half_size,
+                kind: obstacle.kind,
+            });
+
+            let min = Self::cell_of(center - obstacle.half_size);
+            let max = Self::cell_of(center + obstacle.half_size);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+                }
+            }
+        }
+    }
+}
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ObstacleGrid>()
+            .add_systems(
+                Update,
+                (update_obstacle_grid, handle_bullet_obstacle_collision)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                PostUpdate,
+                // Every behaviour moves enemies freely, so push them back out once they have all
+                // run
+                resolve_enemy_obstacle_collisions
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+fn update_obstacle_grid(
+    mut grid: ResMut<ObstacleGrid>,
+    changed_query: Query<(), Changed<Obstacle>>,
+    mut removed: RemovedComponents<Obstacle>,
+    obstacle_query: Query<(&Transform, &Obstacle)>,
+) {
+    if !changed_query.is_empty() || removed.read().next().is_some() {
+        grid.rebuild(&obstacle_query);
+    }
+}
+
+fn resolve_enemy_obstacle_collisions(
+    grid: Res<ObstacleGrid>,
+    mut enemy_query: Query<&mut Transform, With<Enemy>>,
+) {
+    if grid.colliders.is_empty() {
+        return;
+    }
+
+    for mut transform in enemy_query.iter_mut() {
+        // Scaled up enemies such as elites and bosses take up more room
+        let radius = ENEMY_COLLISION_RADIUS * transform.scale.x / SPRITE_SCALE_FACTOR;
+        let position = transform.translation.truncate();
+        let resolved = grid.resolve_circle(position, radius);
+        if resolved != position {
+            transform.translation = resolved.extend(transform.translation.z);
+        }
+    }
+}
+
+fn handle_bullet_obstacle_collision(
+    grid: Res<ObstacleGrid>,
+    mut bullet_query: Query<(Entity, &mut Transform, &mut Bullet)>,
+    mut stop_ew: EventWriter<BulletStopEvent>,
+) {
+    for (entity, mut transform, mut bullet) in bullet_query.iter_mut() {
+        // Sweep the path travelled since the last move so fast bullets cannot skip thin walls
+        let start = bullet.previous_position;
+        let offset = transform.translation.truncate() - start;
+        let Some(direction) = offset.try_normalize() else {
+            continue;
+        };
+        let Some((collider, distance, normal)) = grid.ray_hit(start, direction, offset.length())
+        else {
+            continue;
+        };
+
+        // Bullets that start inside an obstacle have nowhere to bounce to
+        if bullet.on_impact.is_some() || !collider.kind.ricochets() || distance == 0.0 {
+            stop_ew.send(BulletStopEvent {
+                entity,
+                position: start + direction * distance,
+                reason: BulletStop::Impact,
+            });
+            continue;
+        }
+
+        let velocity = bullet.velocity.truncate();
+        let reflected = velocity - 2.0 * velocity.dot(normal) * normal;
+        bullet.velocity = reflected.extend(0.0);
+        // Step back out of the face so the bullet does not bounce again next frame
+        let surface = start + direction * distance + normal;
+        bullet.previous_position = surface;
+        transform.translation = surface.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(reflected.to_angle() - PI / 2.0);
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    collision::{CollisionLayer, EnemyKdTree},
+    constants::*,
+    damage::{Damage, DamageType},
+    enemy::EnemyDamageEvent,
+    gun::{spawn_bullet, Bullet},
+    player::Player,
+    resources::GameResourceSpriteAtlas,
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use std::f32::consts::{PI, TAU};
+
+/// Weapons that fire on their own alongside the player's gun.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum PassiveKind {
+    OrbitBlades,
+    Aura,
+    AutoTurret,
+}
+
+impl PassiveKind {
+    pub const ALL: [PassiveKind; 3] = [
+        PassiveKind::OrbitBlades,
+        PassiveKind::Aura,
+        PassiveKind::AutoTurret,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PassiveKind::OrbitBlades => "Orbit Blades",
+            PassiveKind::Aura => "Aura",
+            PassiveKind::AutoTurret => "Auto Turret",
+        }
+    }
+
+    /// Damage of each hit at `level`, starting from 1.
+    pub fn damage(&self, level: u32) -> Damage {
+        let bonus = (level - 1) as f32;
+        match self {
+            PassiveKind::OrbitBlades => Damage::new(
+                ORBIT_BLADE_DAMAGE + ORBIT_BLADE_DAMAGE_PER_LEVEL * bonus,
+                DamageType::Physical,
+            ),
+            PassiveKind::Aura => Damage::new(
+                AURA_DAMAGE + AURA_DAMAGE_PER_LEVEL * bonus,
+                DamageType::Fire,
+            ),
+            PassiveKind::AutoTurret => Damage::new(
+                TURRET_DAMAGE + TURRET_DAMAGE_PER_LEVEL * bonus,
+                DamageType::Physical,
+            ),
+        }
+    }
+
+    /// Seconds between hits or shots at `level`.
+    pub fn cooldown(&self, level: u32) -> f32 {
+        match self {
+            PassiveKind::OrbitBlades => ORBIT_BLADE_HIT_INTERVAL,
+            PassiveKind::Aura => AURA_TICK_RATE,
+            PassiveKind::AutoTurret => {
+                TURRET_FIRE_RATE * TURRET_FIRE_RATE_SCALING.powi(level as i32 - 1)
+            }
+        }
+    }
+
+    fn aura_radius(level: u32) -> f32 {
+        AURA_RADIUS + AURA_RADIUS_PER_LEVEL * (level - 1) as f32
+    }
+
+    fn blade_count(level: u32) -> u32 {
+        level + 1
+    }
+}
+
+#[derive(Component)]
+pub struct PassiveWeapon {
+    pub kind: PassiveKind,
+    pub level: u32,
+    pub cooldown: Timer,
+}
+
+/// Adds a passive weapon the player does not have yet, or levels up the one they have.
+#[derive(Event)]
+pub struct GrantPassiveEvent(pub PassiveKind);
+
+/// A single blade circling the player, one of `PassiveKind::blade_count` spread evenly around.
+#[derive(Component)]
+struct OrbitBlade {
+    index: u32,
+}
+
+pub struct PassivePlugin;
+
+impl Plugin for PassivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GrantPassiveEvent>().add_systems(
+            Update,
+            (
+                (handle_grant_passive, sync_orbit_blades).chain(),
+                tick_passive_cooldowns,
+                // Hits and shots fire on the tick the cooldown finishes
+                (update_orbit_blades, update_aura, update_auto_turret)
+                    .after(tick_passive_cooldowns),
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn handle_grant_passive(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    mut events: EventReader<GrantPassiveEvent>,
+    mut passive_query: Query<&mut PassiveWeapon>,
+) {
+    // New passives only exist once the commands are applied, so grants of a kind that is not
+    // spawned yet are counted up first and spawned at the level they add up to
+    let mut new_levels = HashMap::<PassiveKind, u32>::new();
+    for GrantPassiveEvent(kind) in events.read() {
+        if let Some(mut passive) = passive_query.iter_mut().find(|p| p.kind == *kind) {
+            passive.level = (passive.level + 1).min(PASSIVE_MAX_LEVEL);
+            let cooldown = kind.cooldown(passive.level);
+            passive
+                .cooldown
+                .set_duration(Duration::from_secs_f32(cooldown));
+            continue;
+        }
+        *new_levels.entry(*kind).or_default() += 1;
+    }
+
+    for (kind, level) in new_levels {
+        let level = level.min(PASSIVE_MAX_LEVEL);
+        let mut entity = commands.spawn((
+            PassiveWeapon {
+                kind,
+                level,
+                cooldown: Timer::from_seconds(kind.cooldown(level), TimerMode::Repeating),
+            },
+            Name::new(kind.name()),
+            GameEntity,
+        ));
+        // The turret is the only passive with a body of its own
+        if kind == PassiveKind::AutoTurret {
+            entity.insert((
+                SpriteBundle {
+                    texture: game_resource.sprite_sheet.clone().unwrap(),
+                    sprite: Sprite {
+                        color: Color::srgb(0.6, 0.9, 1.0),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(0.0, 0.0, 3.0))
+                        .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                    ..default()
+                },
+                TextureAtlas {
+                    layout: game_resource.atlas_layout.clone().unwrap(),
+                    index: 0,
+                },
+            ));
+        }
+    }
+}
+
+/// Respawns the blades whenever the orbit blades are gained or levelled up.
+fn sync_orbit_blades(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    passive_query: Query<&PassiveWeapon>,
+    blade_query: Query<Entity, With<OrbitBlade>>,
+) {
+    let Some(passive) = passive_query
+        .iter()
+        .find(|passive| passive.kind == PassiveKind::OrbitBlades)
+    else {
+        return;
+    };
+    // Cooldown ticks touch the component every frame, so compare blade counts instead of
+    // relying on change detection
+    if blade_query.iter().len() == PassiveKind::blade_count(passive.level) as usize {
+        return;
+    }
+
+    for entity in blade_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    for index in 0..PassiveKind::blade_count(passive.level) {
+        commands.spawn((
+            SpriteBundle {
+                texture: game_resource.sprite_sheet.clone().unwrap(),
+                sprite: Sprite {
+                    color: Color::srgb(0.85, 0.85, 0.95),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 3.0))
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR * 2.0)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: game_resource.atlas_layout.clone().unwrap(),
+                index: 1,
+            },
+            OrbitBlade { index },
+            GameEntity,
+        ));
+    }
+}
+
+fn tick_passive_cooldowns(time: Res<Time>, mut passive_query: Query<&mut PassiveWeapon>) {
+    for mut passive in passive_query.iter_mut() {
+        passive.cooldown.tick(time.delta());
+    }
+}
+
+/// Sends one damage event to every enemy within `radius` of `position`.
+fn damage_enemies_around(
+    tree: &EnemyKdTree,
+    ew: &mut EventWriter<EnemyDamageEvent>,
+    position: Vec2,
+    radius: f32,
+    damage: Damage,
+) {
+    for enemy in tree.within_radius(position, radius) {
+        let (amount, crit) = damage.roll();
+        ew.send(EnemyDamageEvent {
+            entity: enemy.entity,
+            amount,
+            damage_type: damage.damage_type,
+            crit,
+        });
+    }
+}
+
+fn update_orbit_blades(
+    time: Res<Time>,
+    tree: Res<EnemyKdTree>,
+    player_query: Query<&Transform, With<Player>>,
+    passive_query: Query<&PassiveWeapon>,
+    mut blade_query: Query<(&mut Transform, &OrbitBlade), Without<Player>>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Some(passive) = passive_query
+        .iter()
+        .find(|passive| passive.kind == PassiveKind::OrbitBlades)
+    else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let count = PassiveKind::blade_count(passive.level);
+    let rotation = time.elapsed_seconds() * ORBIT_BLADE_SPEED;
+    for (mut transform, blade) in blade_query.iter_mut() {
+        let angle = rotation + TAU * blade.index as f32 / count as f32;
+        let position = player_pos + Vec2::from_angle(angle) * ORBIT_BLADE_DISTANCE;
+        transform.translation = position.extend(transform.translation.z);
+        // Blades face along their path
+        transform.rotation = Quat::from_rotation_z(angle);
+
+        if passive.cooldown.just_finished() {
+            damage_enemies_around(
+                &tree,
+                &mut ew,
+                position,
+                ORBIT_BLADE_HIT_RADIUS,
+                passive.kind.damage(passive.level),
+            );
+        }
+    }
+}
+
+fn update_aura(
+    mut gizmos: Gizmos,
+    tree: Res<EnemyKdTree>,
+    player_query: Query<&Transform, With<Player>>,
+    passive_query: Query<&PassiveWeapon>,
+    mut ew: EventWriter<EnemyDamageEvent>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Some(passive) = passive_query
+        .iter()
+        .find(|passive| passive.kind == PassiveKind::Aura)
+    else {
+        return;
+    };
+
+    let player_pos = player_transform.translation.truncate();
+    let radius = PassiveKind::aura_radius(passive.level);
+    let pulse = 1.0 - passive.cooldown.fraction();
+    gizmos.circle_2d(
+        player_pos,
+        radius,
+        Color::srgba(1.0, 0.55, 0.2, 0.2 + 0.4 * pulse),
+    );
+
+    if passive.cooldown.just_finished() {
+        damage_enemies_around(
+            &tree,
+            &mut ew,
+            player_pos,
+            radius,
+            passive.kind.damage(passive.level),
+        );
+    }
+}
+
+fn update_auto_turret(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    tree: Res<EnemyKdTree>,
+    player_query: Query<&Transform, With<Player>>,
+    mut turret_query: Query<(&mut Transform, &PassiveWeapon), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (mut transform, passive) in turret_query.iter_mut() {
+        if passive.kind != PassiveKind::AutoTurret {
+            continue;
+        }
+        let position = player_pos + Vec2::new(-TURRET_OFFSET, TURRET_OFFSET);
+        transform.translation = position.extend(transform.translation.z);
+
+        let Some(nearest) = tree
+            .nearest(position)
+            .filter(|enemy| enemy.distance_to(position) <= TURRET_RANGE)
+        else {
+            continue;
+        };
+        let direction = nearest.pos - position;
+        transform.rotation = Quat::from_rotation_z(direction.to_angle() - PI / 2.0);
+
+        if passive.cooldown.just_finished() {
+            spawn_bullet(
+                &mut commands,
+                &game_resource,
+                transform.translation,
+                Bullet::new(
+                    direction,
+                    BULLET_SPEED,
+                    CollisionLayer::Player,
+                    passive.kind.damage(passive.level),
+                ),
+            );
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    boss::BossDeath,
+    constants::*,
+    effects::DeathEffect,
+    elite::Elite,
+    gun::{spawn_weapon, Ammo, Weapon, WeaponInventory, WeaponKind},
+    minimap::MinimapMarker,
+    player::Player,
+    resources::GameResourceSpriteAtlas,
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use rand::{seq::SliceRandom, Rng};
+
+/// A weapon lying in the world, picked up by walking over it.
+#[derive(Component)]
+pub struct WeaponPickup(pub WeaponKind);
+
+/// Tops up the reserve ammo of every carried weapon that uses ammo.
+#[derive(Component)]
+pub struct AmmoPickup;
+
+/// Despawns a pickup that was left lying around for too long.
+#[derive(Component)]
+struct PickupLifetime(Timer);
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                drop_weapons,
+                drop_ammo,
+                collect_weapon_pickups,
+                collect_ammo_pickups,
+                expire_pickups,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn spawn_pickup<'a>(
+    commands: &'a mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    index: usize,
+    color: Color,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        SpriteBundle {
+            texture: game_resource.sprite_sheet.clone().unwrap(),
+            sprite: Sprite { color, ..default() },
+            transform: Transform::from_translation(position.extend(2.0))
+                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+            ..default()
+        },
+        TextureAtlas {
+            layout: game_resource.atlas_layout.clone().unwrap(),
+            index,
+        },
+        PickupLifetime(Timer::from_seconds(PICKUP_LIFETIME, TimerMode::Once)),
+        MinimapMarker {
+            color: Color::srgb(0.3, 1.0, 0.4),
+            size: 3,
+        },
+        GameEntity,
+    ))
+}
+
+pub fn spawn_weapon_pickup(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    kind: WeaponKind,
+    position: Vec2,
+) -> Entity {
+    spawn_pickup(
+        commands,
+        game_resource,
+        kind.icon_sprite_index(),
+        Color::WHITE,
+        position,
+    )
+    .insert(WeaponPickup(kind))
+    .id()
+}
+
+/// Ammo uses the bullet sprite, tinted so it stands out from live bullets.
+pub fn spawn_ammo_pickup(
+    commands: &mut Commands,
+    game_resource: &GameResourceSpriteAtlas,
+    position: Vec2,
+) -> Entity {
+    spawn_pickup(
+        commands,
+        game_resource,
+        1,
+        Color::srgb(1.0, 0.8, 0.2),
+        position,
+    )
+    .insert(AmmoPickup)
+    .id()
+}
+
+/// Enemies occasionally drop a weapon the player does not carry yet. Elites drop more often and
+/// bosses always do.
+fn drop_weapons(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    inventory: Res<WeaponInventory>,
+    weapon_query: Query<&Weapon>,
+    dead_query: Query<(&Transform, Option<&Elite>), Added<DeathEffect>>,
+    boss_query: Query<&Transform, Added<BossDeath>>,
+) {
+    let owned: Vec<WeaponKind> = inventory
+        .slots
+        .iter()
+        .filter_map(|&entity| weapon_query.get(entity).ok())
+        .map(|weapon| weapon.kind)
+        .collect();
+    let candidates: Vec<WeaponKind> = WeaponKind::ALL
+        .into_iter()
+        .filter(|kind| !owned.contains(kind))
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    let enemy_drops = dead_query.iter().filter_map(|(transform, elite)| {
+        let chance = if elite.is_some() {
+            ELITE_WEAPON_DROP_CHANCE
+        } else {
+            WEAPON_DROP_CHANCE
+        };
+        rng.gen_bool(chance).then_some(transform)
+    });
+    let drops: Vec<Vec2> = enemy_drops
+        .chain(boss_query.iter())
+        .map(|transform| transform.translation.truncate())
+        .collect();
+
+    for position in drops {
+        let Some(&kind) = candidates.choose(&mut rng) else {
+            return;
+        };
+        spawn_weapon_pickup(&mut commands, &game_resource, kind, position);
+    }
+}
+
+fn drop_ammo(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    dead_query: Query<&Transform, Added<DeathEffect>>,
+) {
+    let mut rng = rand::thread_rng();
+    for transform in dead_query.iter() {
+        if rng.gen_bool(AMMO_DROP_CHANCE) {
+            spawn_ammo_pickup(
+                &mut commands,
+                &game_resource,
+                transform.translation.truncate(),
+            );
+        }
+    }
+}
+
+fn collect_weapon_pickups(
+    mut commands: Commands,
+    game_resource: Res<GameResourceSpriteAtlas>,
+    mut inventory: ResMut<WeaponInventory>,
+    mut weapon_query: Query<(&Weapon, Option<&mut Ammo>)>,
+    player_query: Query<&Transform, With<Player>>,
+    pickup_query: Query<(Entity, &Transform, &WeaponPickup)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (entity, transform, pickup) in pickup_query.iter() {
+        if transform.translation.truncate().distance(player_pos) > PICKUP_RADIUS {
+            continue;
+        }
+
+        let owned = inventory
+            .slots
+            .iter()
+            .find(|&&slot| {
+                weapon_query
+                    .get(slot)
+                    .is_ok_and(|(weapon, _)| weapon.kind == pickup.0)
+            })
+            .copied();
+        if let Some(slot) = owned {
+            // A duplicate refills the copy already carried, and stays on the ground if that has
+            // nothing to refill
+            match weapon_query.get_mut(slot) {
+                Ok((_, Some(mut ammo))) if ammo.reserve < ammo.max_reserve => {
+                    let max_reserve = ammo.max_reserve;
+                    ammo.add_reserve(max_reserve);
+                    commands.entity(entity).despawn();
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        let weapon = spawn_weapon(&mut commands, &game_resource, pickup.0);
+        // A full inventory swaps out the weapon in hand
+        if let Some(replaced) = inventory.add(weapon) {
+            commands.entity(replaced).despawn();
+        }
+    }
+}
+
+fn collect_ammo_pickups(
+    mut commands: Commands,
+    inventory: Res<WeaponInventory>,
+    mut ammo_query: Query<&mut Ammo>,
+    player_query: Query<&Transform, With<Player>>,
+    pickup_query: Query<(Entity, &Transform), With<AmmoPickup>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = player_transform.translation.truncate();
+
+    for (entity, transform) in pickup_query.iter() {
+        if transform.translation.truncate().distance(player_pos) > PICKUP_RADIUS {
+            continue;
+        }
+        // Left on the ground while every weapon is full so it is not wasted
+        let mut carried = ammo_query.iter_many_mut(&inventory.slots);
+        let mut collected = false;
+        while let Some(mut ammo) = carried.fetch_next() {
+            if ammo.reserve < ammo.max_reserve {
+                let refill = (ammo.max_reserve as f32 * AMMO_PICKUP_REFILL).ceil() as u32;
+                ammo.add_reserve(refill);
+                collected = true;
+            }
+        }
+        if collected {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickup_query: Query<(Entity, &mut PickupLifetime)>,
+) {
+    for (entity, mut lifetime) in pickup_query.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
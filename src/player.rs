@@ -1,4 +1,11 @@
-use crate::{constants::*, resources::PlayerHealth, state::*};
+use crate::{
+    camera::CameraTrauma,
+    constants::*,
+    obstacle::ObstacleGrid,
+    resources::{PlayerHealth, WorldBounds},
+    state::*,
+    status::StatusEffects,
+};
 use bevy::{math::vec3, prelude::*};
 
 #[derive(Component)]
@@ -12,35 +19,41 @@ pub enum PlayerState {
 }
 
 #[derive(Event)]
-pub struct PlayerEnemyCollisionEvent;
+pub struct PlayerDamageEvent {
+    pub amount: f32,
+}
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<PlayerEnemyCollisionEvent>().add_systems(
+        app.add_event::<PlayerDamageEvent>().add_systems(
             Update,
             (
                 handle_player_death,
                 handle_player_input,
-                handle_player_enemy_collision_events,
+                handle_player_damage_events,
             )
                 .run_if(in_state(GameState::InGame)),
         );
     }
 }
 
-fn handle_player_enemy_collision_events(
+fn handle_player_damage_events(
     mut player_health: ResMut<PlayerHealth>,
-    mut events: EventReader<PlayerEnemyCollisionEvent>,
+    mut events: EventReader<PlayerDamageEvent>,
+    mut trauma: ResMut<CameraTrauma>,
 ) {
-    for _ in events.read() {
-        player_health.as_mut().value -= ENEMY_DAMAGE;
+    for event in events.read() {
+        player_health.as_mut().value -= event.amount;
+        trauma.add(PLAYER_HIT_TRAUMA);
     }
 }
 
 fn handle_player_input(
-    mut player_query: Query<(&mut Transform, &mut PlayerState), With<Player>>,
+    mut player_query: Query<(&mut Transform, &mut PlayerState, &StatusEffects), With<Player>>,
+    obstacle_grid: Res<ObstacleGrid>,
+    world_bounds: Res<WorldBounds>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
 ) {
@@ -48,29 +61,37 @@ fn handle_player_input(
         return;
     }
 
-    let (mut transform, mut state) = player_query.single_mut();
+    let (mut transform, mut state, status) = player_query.single_mut();
     let up = keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
     let down = keyboard_input.any_pressed([KeyCode::KeyR, KeyCode::ArrowDown]);
     let left = keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
     let right = keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowRight]);
 
     let mut delta = Vec2::ZERO;
-    if up && transform.translation.y < WORLD_H {
+    if up {
         delta.y += 1.0;
     }
-    if down && transform.translation.y > -WORLD_H {
+    if down {
         delta.y -= 1.0;
     }
-    if left && transform.translation.x > -WORLD_W {
+    if left {
         delta.x -= 1.0;
     }
-    if right && transform.translation.x < WORLD_W {
+    if right {
         delta.x += 1.0;
     }
     delta = delta.normalize();
 
     if delta.is_finite() {
-        transform.translation += vec3(delta.x, delta.y, 0.0) * PLAYER_SPEED * time.delta_seconds();
+        transform.translation += vec3(delta.x, delta.y, 0.0)
+            * PLAYER_SPEED
+            * status.speed_multiplier()
+            * time.delta_seconds();
+        let resolved = obstacle_grid.resolve_circle(
+            world_bounds.clamp(transform.translation.truncate()),
+            PLAYER_COLLISION_RADIUS,
+        );
+        transform.translation = resolved.extend(transform.translation.z);
         *state = PlayerState::Run;
     } else {
         *state = PlayerState::Idle;
@@ -32,8 +32,72 @@ pub struct CursorPos(pub Option<Vec2>);
 #[derive(Resource)]
 pub struct PlayerHealth {
     pub value: f32,
+    pub max: f32,
 }
 
+#[derive(Resource, Default)]
+pub struct PlayerExperience {
+    pub value: f32,
+    pub level: u32,
+}
+
+impl PlayerExperience {
+    /// Experience needed to go from the current level to the next one.
+    pub fn next_level_xp(&self) -> f32 {
+        XP_BASE_LEVEL_COST * (self.level + 1) as f32
+    }
+
+    pub fn add(&mut self, amount: f32) {
+        self.value += amount;
+        while self.value >= self.next_level_xp() {
+            self.value -= self.next_level_xp();
+            self.level += 1;
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub elapsed: f32,
+    pub kills: u32,
+    pub wave: u32,
+}
+
+/// Playable area centred on the origin. Without a `half_size` the world streams in endlessly
+/// around the player.
+#[derive(Resource, Clone, Copy)]
+pub struct WorldBounds {
+    pub half_size: Option<Vec2>,
+}
+
+impl Default for WorldBounds {
+    fn default() -> Self {
+        Self {
+            half_size: Some(Vec2::new(WORLD_W, WORLD_H)),
+        }
+    }
+}
+
+impl WorldBounds {
+    pub fn unbounded() -> Self {
+        Self { half_size: None }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.half_size
+            .is_none_or(|half| position.x.abs() <= half.x && position.y.abs() <= half.y)
+    }
+
+    pub fn clamp(&self, position: Vec2) -> Vec2 {
+        self.half_size
+            .map_or(position, |half| position.clamp(-half, half))
+    }
+}
+
+/// Seed every procedurally generated part of the world is derived from.
+#[derive(Resource, Default)]
+pub struct WorldSeed(pub u64);
+
 pub struct ResourcesPlugin;
 
 impl Plugin for ResourcesPlugin {
@@ -46,6 +110,8 @@ impl Plugin for ResourcesPlugin {
             atlas_layout: None,
             sprite_sheet: None,
         })
+        .init_resource::<WorldSeed>()
+        .init_resource::<WorldBounds>()
         .insert_resource(GameDecorationSpriteAtlas {
             atlas_layout: None,
             sprite_sheet: None,
@@ -55,7 +121,12 @@ impl Plugin for ResourcesPlugin {
             entity_sheets: vec![None; 6],
         })
         .insert_resource(CursorPos(None))
-        .insert_resource(PlayerHealth { value: 100.0 })
+        .insert_resource(PlayerHealth {
+            value: PLAYER_MAX_HEALTH,
+            max: PLAYER_MAX_HEALTH,
+        })
+        .insert_resource(PlayerExperience::default())
+        .insert_resource(RunStats::default())
         .add_systems(OnEnter(GameState::Loading), load_assets)
         .add_systems(
             Update,
@@ -90,7 +161,7 @@ fn load_assets(
 
     let resource_layout = TextureAtlasLayout::from_grid(
         UVec2::splat(SPRITE_TILE_SIZE),
-        6,
+        14,
         1,
         None,
         Some(UVec2::splat(1)),
@@ -0,0 +1,93 @@
+use crate::{constants::*, obstacle::ObstacleGrid, resources::WorldBounds};
+use bevy::prelude::*;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+/// Circular area enemies may spawn in, centred on the entity's transform.
+#[derive(Component, Clone, Copy)]
+pub struct SpawnZone {
+    pub radius: f32,
+    /// Relative chance of this zone being picked over the others.
+    pub weight: f32,
+    pub min_player_distance: f32,
+    /// Most enemies from this zone that may be alive at once.
+    pub max_enemies: Option<usize>,
+    /// Only spawn where the player's camera cannot see.
+    pub offscreen_only: bool,
+}
+
+/// The zone an enemy was spawned from, used to enforce `SpawnZone::max_enemies`.
+#[derive(Component)]
+pub struct SpawnedBy(pub Entity);
+
+/// Everything a candidate spawn position is checked against.
+pub struct SpawnRules<'a> {
+    pub player_position: Vec2,
+    /// Area currently visible to the player's camera.
+    pub view: Rect,
+    pub world_bounds: &'a WorldBounds,
+    pub obstacles: &'a ObstacleGrid,
+}
+
+impl SpawnRules<'_> {
+    fn allows(&self, position: Vec2, min_player_distance: f32, offscreen_only: bool) -> bool {
+        let view = self.view.inflate(SPAWN_OFFSCREEN_MARGIN);
+        self.world_bounds.contains(position)
+            && position.distance(self.player_position) >= min_player_distance
+            && !(offscreen_only && view.contains(position))
+            && !self.obstacles.blocks(position, ENEMY_COLLISION_RADIUS)
+    }
+
+    /// Picks a spawn position from `zones`, weighted and skipping any zone at its `max_enemies`.
+    /// Without zones enemies spawn in a ring around the player. Returns the position and the zone
+    /// it came from, or `None` when no valid position was found in `SPAWN_ATTEMPTS` tries.
+    pub fn pick(
+        &self,
+        rng: &mut impl Rng,
+        zones: &[(Entity, Vec2, SpawnZone, usize)],
+    ) -> Option<(Vec2, Option<Entity>)> {
+        if zones.is_empty() {
+            return (0..SPAWN_ATTEMPTS)
+                .map(|_| {
+                    let angle = rng.gen_range(0.0..TAU);
+                    let distance =
+                        rng.gen_range(SPAWN_MIN_PLAYER_DISTANCE..SPAWN_MAX_PLAYER_DISTANCE);
+                    self.player_position + Vec2::from_angle(angle) * distance
+                })
+                .find(|&position| self.allows(position, SPAWN_MIN_PLAYER_DISTANCE, true))
+                .map(|position| (position, None));
+        }
+
+        let open = |zone: &SpawnZone, alive: usize| zone.max_enemies.is_none_or(|max| alive < max);
+        let total_weight: f32 = zones
+            .iter()
+            .filter(|(_, _, zone, alive)| open(zone, *alive))
+            .map(|(_, _, zone, _)| zone.weight)
+            .sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        for _ in 0..SPAWN_ATTEMPTS {
+            let mut roll = rng.gen_range(0.0..total_weight);
+            let Some((entity, center, zone, _)) = zones
+                .iter()
+                .filter(|(_, _, zone, alive)| open(zone, *alive))
+                .find(|(_, _, zone, _)| {
+                    roll -= zone.weight;
+                    roll < 0.0
+                })
+            else {
+                continue;
+            };
+
+            // Square root keeps points uniform over the disc instead of bunching at the centre
+            let distance = zone.radius * rng.gen_range(0.0f32..1.0).sqrt();
+            let position = *center + Vec2::from_angle(rng.gen_range(0.0..TAU)) * distance;
+            if self.allows(position, zone.min_player_distance, zone.offscreen_only) {
+                return Some((position, Some(*entity)));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    constants::*,
+    damage::DamageType,
+    effects::{BaseTint, HitFlash},
+    enemy::{Enemy, EnemyDamageEvent},
+    player::{Player, PlayerDamageEvent},
+    state::GameState,
+};
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusKind {
+    /// Damage over time. Reapplying refreshes the duration and keeps the strongest burn.
+    Burn,
+    /// Damage over time that stacks up to `POISON_MAX_STACKS`.
+    Poison,
+    /// Speed multiplier. The strongest slow wins.
+    Slow,
+    /// Stops movement entirely.
+    Freeze,
+}
+
+impl StatusKind {
+    fn tint(&self) -> Color {
+        match self {
+            StatusKind::Burn => Color::srgb(1.0, 0.45, 0.2),
+            StatusKind::Poison => Color::srgb(0.5, 1.0, 0.3),
+            StatusKind::Slow => Color::srgb(0.6, 0.75, 1.0),
+            StatusKind::Freeze => Color::srgb(0.6, 1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub duration: f32,
+    /// Damage per second for `Burn` and `Poison`, speed multiplier for `Slow`. Unused by `Freeze`.
+    pub magnitude: f32,
+    stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, duration: f32, magnitude: f32) -> Self {
+        Self {
+            kind,
+            duration,
+            magnitude,
+            stacks: 1,
+        }
+    }
+
+    pub fn burn() -> Self {
+        Self::new(StatusKind::Burn, BURN_DURATION, BURN_DPS)
+    }
+
+    pub fn poison() -> Self {
+        Self::new(StatusKind::Poison, POISON_DURATION, POISON_DPS)
+    }
+
+    pub fn slow() -> Self {
+        Self::new(StatusKind::Slow, SLOW_DURATION, SLOW_MULTIPLIER)
+    }
+
+    pub fn freeze() -> Self {
+        Self::new(StatusKind::Freeze, FREEZE_DURATION, 0.0)
+    }
+}
+
+/// A status effect a projectile or weapon may inflict on hit.
+#[derive(Clone, Copy, Debug)]
+pub struct OnHitStatus {
+    pub effect: StatusEffect,
+    pub chance: f64,
+}
+
+#[derive(Component)]
+pub struct StatusEffects {
+    effects: Vec<StatusEffect>,
+    tick: Timer,
+}
+
+impl Default for StatusEffects {
+    fn default() -> Self {
+        Self {
+            effects: Vec::new(),
+            tick: Timer::from_seconds(STATUS_TICK_RATE, TimerMode::Repeating),
+        }
+    }
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let Some(existing) = self.effects.iter_mut().find(|e| e.kind == effect.kind) else {
+            self.effects.push(effect);
+            return;
+        };
+
+        existing.duration = existing.duration.max(effect.duration);
+        existing.magnitude = match effect.kind {
+            StatusKind::Burn => existing.magnitude.max(effect.magnitude),
+            StatusKind::Slow => existing.magnitude.min(effect.magnitude),
+            StatusKind::Poison | StatusKind::Freeze => existing.magnitude,
+        };
+        if effect.kind == StatusKind::Poison {
+            existing.stacks = (existing.stacks + 1).min(POISON_MAX_STACKS);
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.effects.iter().any(|e| e.kind == kind)
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects
+            .iter()
+            .map(|e| match e.kind {
+                StatusKind::Freeze => 0.0,
+                StatusKind::Slow => e.magnitude,
+                StatusKind::Burn | StatusKind::Poison => 1.0,
+            })
+            .fold(1.0, f32::min)
+    }
+
+    /// Damage per second dealt by every active damage-over-time effect, with its damage type.
+    pub fn damage_over_time(&self) -> impl Iterator<Item = (f32, DamageType)> + '_ {
+        self.effects.iter().filter_map(|e| match e.kind {
+            StatusKind::Burn => Some((e.magnitude, DamageType::Fire)),
+            StatusKind::Poison => Some((e.magnitude * e.stacks as f32, DamageType::Poison)),
+            StatusKind::Slow | StatusKind::Freeze => None,
+        })
+    }
+
+    /// Colour of the most visible active effect, if any.
+    fn tint(&self) -> Option<Color> {
+        [
+            StatusKind::Freeze,
+            StatusKind::Burn,
+            StatusKind::Poison,
+            StatusKind::Slow,
+        ]
+        .into_iter()
+        .find(|&kind| self.has(kind))
+        .map(|kind| kind.tint())
+    }
+}
+
+#[derive(Event)]
+pub struct ApplyStatusEvent {
+    pub entity: Entity,
+    pub effect: StatusEffect,
+}
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyStatusEvent>().add_systems(
+            Update,
+            (
+                apply_status_events,
+                tick_status_effects,
+                update_status_tints,
+            )
+                .run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+fn apply_status_events(
+    mut events: EventReader<ApplyStatusEvent>,
+    mut status_query: Query<&mut StatusEffects>,
+) {
+    for event in events.read() {
+        if let Ok(mut status) = status_query.get_mut(event.entity) {
+            status.apply(event.effect);
+        }
+    }
+}
+
+fn tick_status_effects(
+    time: Res<Time>,
+    mut status_query: Query<(Entity, &mut StatusEffects, Has<Enemy>, Has<Player>)>,
+    mut enemy_ew: EventWriter<EnemyDamageEvent>,
+    mut player_ew: EventWriter<PlayerDamageEvent>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut status, is_enemy, is_player) in status_query.iter_mut() {
+        if status.effects.is_empty() {
+            continue;
+        }
+
+        status.tick.tick(time.delta());
+        if status.tick.just_finished() {
+            for (dps, damage_type) in status.damage_over_time() {
+                let amount = dps * STATUS_TICK_RATE;
+                if is_enemy {
+                    enemy_ew.send(EnemyDamageEvent {
+                        entity,
+                        amount,
+                        damage_type,
+                        crit: false,
+                    });
+                } else if is_player {
+                    player_ew.send(PlayerDamageEvent { amount });
+                }
+            }
+        }
+
+        for effect in status.effects.iter_mut() {
+            effect.duration -= delta;
+        }
+        status.effects.retain(|effect| effect.duration > 0.0);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_status_tints(
+    mut sprite_query: Query<
+        (&mut Sprite, &StatusEffects, Option<&BaseTint>),
+        (
+            Changed<StatusEffects>,
+            Or<(With<Enemy>, With<Player>)>,
+            Without<HitFlash>,
+        ),
+    >,
+) {
+    for (mut sprite, status, base_tint) in sprite_query.iter_mut() {
+        let color = status
+            .tint()
+            .unwrap_or_else(|| base_tint.map_or(Color::WHITE, |tint| tint.0));
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
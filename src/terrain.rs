@@ -0,0 +1,371 @@
+use crate::{
+    constants::*,
+    map::WorldSelection,
+    obstacle::{spawn_obstacle, ObstacleKind},
+    player::Player,
+    resources::{GameDecorationSpriteAtlas, WorldBounds, WorldSeed},
+    state::GameState,
+    world::GameEntity,
+};
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::HashMap,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+const TILE_PIXELS: usize = TERRAIN_TILE_PIXELS as usize;
+const CHUNK_TILES: usize = TERRAIN_CHUNK_TILES as usize;
+const CHUNK_PIXELS: usize = TILE_PIXELS * CHUNK_TILES;
+
+/// Ground types, ordered bottom to top. Higher biomes are drawn over lower ones at transitions.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Biome {
+    Dirt,
+    Grass,
+    Stone,
+}
+
+const BIOMES: [Biome; 3] = [Biome::Dirt, Biome::Grass, Biome::Stone];
+
+/// What may be placed on a tile of a given biome. Chances are rolled once per tile.
+struct BiomeRules {
+    decoration_chance: f64,
+    decorations: &'static [usize],
+    obstacle_chance: f64,
+    obstacles: &'static [ObstacleKind],
+}
+
+impl Biome {
+    fn from_noise(noise: f32) -> Self {
+        if noise < TERRAIN_DIRT_THRESHOLD {
+            Biome::Dirt
+        } else if noise < TERRAIN_STONE_THRESHOLD {
+            Biome::Grass
+        } else {
+            Biome::Stone
+        }
+    }
+
+    /// Base colour and the speckle colour scattered over it.
+    fn colors(&self) -> ([u8; 3], [u8; 3]) {
+        match self {
+            Biome::Dirt => ([150, 120, 85], [125, 98, 70]),
+            Biome::Grass => ([120, 160, 90], [100, 140, 75]),
+            Biome::Stone => ([135, 135, 140], [110, 110, 118]),
+        }
+    }
+
+    fn rules(&self) -> BiomeRules {
+        match self {
+            Biome::Dirt => BiomeRules {
+                decoration_chance: 0.01,
+                decorations: &[1],
+                obstacle_chance: 0.002,
+                obstacles: &[ObstacleKind::Wall, ObstacleKind::Rock],
+            },
+            Biome::Grass => BiomeRules {
+                decoration_chance: 0.05,
+                decorations: &[0, 1],
+                obstacle_chance: 0.004,
+                obstacles: &[ObstacleKind::Tree, ObstacleKind::Tree, ObstacleKind::Rock],
+            },
+            Biome::Stone => BiomeRules {
+                decoration_chance: 0.0,
+                decorations: &[],
+                obstacle_chance: 0.006,
+                obstacles: &[ObstacleKind::Rock, ObstacleKind::Rock, ObstacleKind::Wall],
+            },
+        }
+    }
+}
+
+fn hash(seed: u64, x: i32, y: i32) -> u64 {
+    // SplitMix64 finaliser over the packed coordinates
+    let mut h = seed ^ ((x as u32 as u64) << 32 | y as u32 as u64);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
+
+fn lattice(seed: u64, x: i32, y: i32) -> f32 {
+    (hash(seed, x, y) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Smoothly interpolated random values on an integer lattice, in `0.0..1.0`.
+fn value_noise(seed: u64, p: Vec2) -> f32 {
+    let cell = p.floor();
+    let f = p - cell;
+    let t = f * f * (3.0 - 2.0 * f);
+    let (x, y) = (cell.x as i32, cell.y as i32);
+    let bottom = lattice(seed, x, y).lerp(lattice(seed, x + 1, y), t.x);
+    let top = lattice(seed, x, y + 1).lerp(lattice(seed, x + 1, y + 1), t.x);
+    bottom.lerp(top, t.y)
+}
+
+pub fn biome_at(seed: u64, position: Vec2) -> Biome {
+    let p = position / TERRAIN_NOISE_SCALE;
+    let noise = value_noise(seed, p) * 0.65
+        + value_noise(seed.wrapping_add(1), p * 2.0) * 0.25
+        + value_noise(seed.wrapping_add(2), p * 4.0) * 0.1;
+    Biome::from_noise(noise)
+}
+
+fn tile_biome(seed: u64, tile: IVec2) -> Biome {
+    biome_at(seed, tile.as_vec2() * TERRAIN_TILE_SIZE)
+}
+
+/// Marching squares coverage for each of the 16 corner configurations. Bit 0 is bottom-left,
+/// then bottom-right, top-left and top-right; masks are stored bottom row first.
+fn autotile_masks() -> [[bool; TILE_PIXELS * TILE_PIXELS]; 16] {
+    let mut masks = [[false; TILE_PIXELS * TILE_PIXELS]; 16];
+    for (bits, mask) in masks.iter_mut().enumerate() {
+        let corner = |bit: usize| ((bits >> bit) & 1) as f32;
+        for py in 0..TILE_PIXELS {
+            for px in 0..TILE_PIXELS {
+                let u = (px as f32 + 0.5) / TILE_PIXELS as f32;
+                let v = (py as f32 + 0.5) / TILE_PIXELS as f32;
+                let bottom = corner(0).lerp(corner(1), u);
+                let top = corner(2).lerp(corner(3), u);
+                mask[py * TILE_PIXELS + px] = bottom.lerp(top, v) > 0.5;
+            }
+        }
+    }
+    masks
+}
+
+fn chunk_image(seed: u64, chunk: IVec2) -> Image {
+    let masks = autotile_masks();
+    let first_tile = chunk * TERRAIN_CHUNK_TILES;
+    let corners: Vec<Biome> = (0..=CHUNK_TILES)
+        .flat_map(|y| (0..=CHUNK_TILES).map(move |x| IVec2::new(x as i32, y as i32)))
+        .map(|offset| tile_biome(seed, first_tile + offset))
+        .collect();
+    let corner = |x: usize, y: usize| corners[y * (CHUNK_TILES + 1) + x];
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: CHUNK_PIXELS as u32,
+            height: CHUNK_PIXELS as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+
+    for ty in 0..CHUNK_TILES {
+        for tx in 0..CHUNK_TILES {
+            let tile_corners = [
+                corner(tx, ty),
+                corner(tx + 1, ty),
+                corner(tx, ty + 1),
+                corner(tx + 1, ty + 1),
+            ];
+            let base = *tile_corners.iter().min().unwrap();
+            // One mask per biome layered above the base, covering the corners at or above it
+            let layers: Vec<(Biome, usize)> = BIOMES
+                .into_iter()
+                .filter(|&biome| biome > base)
+                .map(|biome| {
+                    let bits = tile_corners
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &corner_biome)| corner_biome >= biome)
+                        .fold(0, |bits, (i, _)| bits | 1 << i);
+                    (biome, bits)
+                })
+                .collect();
+
+            for py in 0..TILE_PIXELS {
+                for px in 0..TILE_PIXELS {
+                    let biome = layers
+                        .iter()
+                        .rev()
+                        .find(|(_, bits)| masks[*bits][py * TILE_PIXELS + px])
+                        .map(|(biome, _)| *biome)
+                        .unwrap_or(base);
+
+                    let x = tx * TILE_PIXELS + px;
+                    let y = ty * TILE_PIXELS + py;
+                    let (color, speckle) = biome.colors();
+                    let pixel = first_tile * TERRAIN_TILE_PIXELS + IVec2::new(x as i32, y as i32);
+                    let rgb = if hash(seed, pixel.x, pixel.y).is_multiple_of(11) {
+                        speckle
+                    } else {
+                        color
+                    };
+
+                    // Image rows run top to bottom, world y runs bottom to top
+                    let i = ((CHUNK_PIXELS - 1 - y) * CHUNK_PIXELS + x) * 4;
+                    image.data[i..i + 3].copy_from_slice(&rgb);
+                }
+            }
+        }
+    }
+    image
+}
+
+pub fn spawn_decoration(
+    commands: &mut Commands,
+    game_decorations: &GameDecorationSpriteAtlas,
+    position: Vec2,
+    index: usize,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: game_decorations.sprite_sheet.clone().unwrap(),
+                transform: Transform::from_translation(position.extend(1.0))
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                ..default()
+            },
+            TextureAtlas {
+                layout: game_decorations.atlas_layout.clone().unwrap(),
+                index,
+            },
+            GameEntity,
+        ))
+        .id()
+}
+
+/// Spawns the ground image for `chunk`, plus the decorations and obstacles its biomes call for
+/// when `populate` is set, returning every spawned entity. Everything is derived from `seed`, so a
+/// chunk always generates the same way and can be dropped and regenerated freely.
+pub fn spawn_chunk(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    game_decorations: &GameDecorationSpriteAtlas,
+    seed: u64,
+    chunk: IVec2,
+    populate: bool,
+) -> Vec<Entity> {
+    let center = (chunk.as_vec2() + 0.5) * TERRAIN_CHUNK_SIZE;
+    let ground = commands.spawn((
+        SpriteBundle {
+            texture: images.add(chunk_image(seed, chunk)),
+            transform: Transform::from_translation(center.extend(0.0))
+                .with_scale(Vec3::splat(TERRAIN_PIXEL_SCALE)),
+            ..default()
+        },
+        GameEntity,
+    ));
+    let mut entities = vec![ground.id()];
+    if !populate {
+        return entities;
+    }
+
+    let mut rng = StdRng::seed_from_u64(hash(seed, chunk.x, chunk.y));
+    for ty in 0..TERRAIN_CHUNK_TILES {
+        for tx in 0..TERRAIN_CHUNK_TILES {
+            let tile = chunk * TERRAIN_CHUNK_TILES + IVec2::new(tx, ty);
+            let tile_center = (tile.as_vec2() + 0.5) * TERRAIN_TILE_SIZE;
+            let rules = biome_at(seed, tile_center).rules();
+
+            if rng.gen_bool(rules.decoration_chance) {
+                let jitter = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
+                let position = tile_center + jitter * TERRAIN_TILE_SIZE;
+                let index = *rules.decorations.choose(&mut rng).unwrap();
+                entities.push(spawn_decoration(
+                    commands,
+                    game_decorations,
+                    position,
+                    index,
+                ));
+            }
+
+            // Keep the player's starting area open
+            if rng.gen_bool(rules.obstacle_chance)
+                && tile_center.length() > OBSTACLE_SPAWN_CLEARANCE
+            {
+                let kind = *rules.obstacles.choose(&mut rng).unwrap();
+                let half_size = kind.random_half_size(&mut rng);
+                entities.push(spawn_obstacle(commands, kind, half_size, tile_center));
+            }
+        }
+    }
+    entities
+}
+
+/// Chunks currently spawned, with the entities belonging to each.
+#[derive(Resource, Default)]
+struct LoadedChunks(HashMap<IVec2, Vec<Entity>>);
+
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadedChunks>()
+            .add_systems(OnEnter(GameState::GameInit), reset_loaded_chunks)
+            .add_systems(Update, stream_chunks.run_if(in_state(GameState::InGame)));
+    }
+}
+
+fn reset_loaded_chunks(mut loaded: ResMut<LoadedChunks>) {
+    // Chunk entities are game entities, so they are already gone once a run ends
+    loaded.0.clear();
+}
+
+fn chunk_in_bounds(chunk: IVec2, world_bounds: &WorldBounds) -> bool {
+    world_bounds.half_size.is_none_or(|half| {
+        let min = chunk.as_vec2() * TERRAIN_CHUNK_SIZE;
+        let max = min + TERRAIN_CHUNK_SIZE;
+        min.x < half.x && max.x > -half.x && min.y < half.y && max.y > -half.y
+    })
+}
+
+/// Generates missing chunks around the player nearest first, a few per frame to avoid hitches,
+/// and drops chunks that have fallen well behind.
+#[allow(clippy::too_many_arguments)]
+fn stream_chunks(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut loaded: ResMut<LoadedChunks>,
+    game_decorations: Res<GameDecorationSpriteAtlas>,
+    world_seed: Res<WorldSeed>,
+    world_bounds: Res<WorldBounds>,
+    world_selection: Res<WorldSelection>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let center = (player_transform.translation.truncate() / TERRAIN_CHUNK_SIZE)
+        .floor()
+        .as_ivec2();
+
+    loaded.0.retain(|chunk, entities| {
+        let keep = (*chunk - center).abs().max_element() <= TERRAIN_UNLOAD_RADIUS;
+        if !keep {
+            for &entity in entities.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+        keep
+    });
+
+    let radius = TERRAIN_LOAD_RADIUS;
+    let mut missing: Vec<IVec2> = (-radius..=radius)
+        .flat_map(|y| (-radius..=radius).map(move |x| center + IVec2::new(x, y)))
+        .filter(|chunk| !loaded.0.contains_key(chunk) && chunk_in_bounds(*chunk, &world_bounds))
+        .collect();
+    missing.sort_by_key(|chunk| (*chunk - center).length_squared());
+
+    // Hand-authored maps place their own obstacles and decorations
+    let populate = !matches!(*world_selection, WorldSelection::Map(_));
+    for chunk in missing.into_iter().take(TERRAIN_CHUNKS_PER_FRAME) {
+        let entities = spawn_chunk(
+            &mut commands,
+            &mut images,
+            &game_decorations,
+            world_seed.0,
+            chunk,
+            populate,
+        );
+        loaded.0.insert(chunk, entities);
+    }
+}
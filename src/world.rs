@@ -2,11 +2,15 @@ use crate::{
     animations::AnimationTimer,
     constants::*,
     gun::*,
+    map::{spawn_map, MapDefinition, MapList, WorldSelection},
+    melee::MeleeCooldown,
+    minimap::MinimapMarker,
     player::{Player, PlayerState},
     resources::*,
     state::*,
+    status::StatusEffects,
 };
-use bevy::{math::vec3, prelude::*};
+use bevy::prelude::*;
 use rand::Rng;
 
 #[derive(Component)]
@@ -16,25 +20,53 @@ pub struct WorldPlugin;
 
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::GameInit),
-            (init_world, spawn_world_decoration),
-        )
-        .add_systems(OnExit(GameState::InGame), despawn_all_game_entities);
+        app.add_systems(OnEnter(GameState::GameInit), init_world)
+            .add_systems(Update, update_run_stats.run_if(in_state(GameState::InGame)))
+            .add_systems(OnExit(GameState::InGame), despawn_all_game_entities);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn init_world(
     mut commands: Commands,
     game_entities: Res<GameEntitySpriteAtlas>,
     game_resources: Res<GameResourceSpriteAtlas>,
+    game_decorations: Res<GameDecorationSpriteAtlas>,
+    map_list: Res<MapList>,
+    maps: Res<Assets<MapDefinition>>,
+    mut world_selection: ResMut<WorldSelection>,
+    mut world_bounds: ResMut<WorldBounds>,
     mut player_health: ResMut<PlayerHealth>,
+    mut player_xp: ResMut<PlayerExperience>,
+    mut run_stats: ResMut<RunStats>,
+    mut world_seed: ResMut<WorldSeed>,
+    mut weapon_inventory: ResMut<WeaponInventory>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
+    let map = world_selection.map(&map_list, &maps);
+    if map.is_none() && matches!(*world_selection, WorldSelection::Map(_)) {
+        warn!("Selected map has not loaded, falling back to a generated world");
+        *world_selection = WorldSelection::Bounded;
+    }
+    *world_bounds = match (map, *world_selection) {
+        (Some(map), _) => WorldBounds {
+            half_size: Some(map.half_size),
+        },
+        (None, WorldSelection::Infinite) => WorldBounds::unbounded(),
+        (None, _) => WorldBounds::default(),
+    };
+    world_seed.0 = map
+        .and_then(|map| map.seed)
+        .unwrap_or_else(|| rand::thread_rng().gen());
+    let player_start = map.map_or(Vec2::ZERO, |map| map.player_start);
+    if let Some(map) = map {
+        spawn_map(&mut commands, &game_decorations, map);
+    }
+
     commands.spawn((
         SpriteBundle {
             texture: game_entities.entity_sheets[0].clone().unwrap(), //global_sprite.sprite_sheet.clone().unwrap(),
-            transform: Transform::from_translation(vec3(0.0, 0.0, 3.0))
+            transform: Transform::from_translation(player_start.extend(3.0))
                 .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
             ..default()
         },
@@ -44,55 +76,30 @@ fn init_world(
         },
         Player,
         PlayerState::default(),
+        StatusEffects::default(),
+        MeleeCooldown::default(),
+        MinimapMarker {
+            color: Color::srgb(0.2, 0.6, 1.0),
+            size: 4,
+        },
         AnimationTimer(Timer::from_seconds(0.125, TimerMode::Repeating)),
         GameEntity,
     ));
 
-    commands.spawn((
-        SpriteBundle {
-            texture: game_resources.sprite_sheet.clone().unwrap(), //global_sprite.sprite_sheet.clone().unwrap(),
-            transform: Transform::from_translation(vec3(0.0, 0.0, 3.0))
-                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            ..default()
-        },
-        TextureAtlas {
-            layout: game_resources.atlas_layout.clone().unwrap(), //global_sprite.texture_atlas.clone().unwrap(),
-            index: 0,
-        },
-        Gun,
-        GunCooldown(Timer::from_seconds(GUN_FIRE_RATE, TimerMode::Once)),
-        GameEntity,
-    ));
+    let shotgun = spawn_weapon(&mut commands, &game_resources, WeaponKind::Shotgun);
+    *weapon_inventory = WeaponInventory::default();
+    weapon_inventory.add(shotgun);
 
-    player_health.value = 100.0;
+    player_health.value = player_health.max;
+    *player_xp = PlayerExperience::default();
+    *run_stats = RunStats::default();
 
     next_state.set(GameState::InGame);
 }
 
-fn spawn_world_decoration(
-    mut commands: Commands,
-    game_decorations: Res<GameDecorationSpriteAtlas>,
-    //global_sprite: Res<GlobalSpriteTextureHandle>, // texture_atlas: Res<GlobalTextureAtlasHandle>,
-    // image_handle: Res<GlobalSpriteSheetHandle>,
-) {
-    let mut rng = rand::thread_rng();
-    for _ in 0..NUM_WORLD_DECORATIONS {
-        let x = rng.gen_range(-WORLD_W..WORLD_W);
-        let y = rng.gen_range(-WORLD_H..WORLD_H);
-        commands.spawn((
-            SpriteBundle {
-                texture: game_decorations.sprite_sheet.clone().unwrap(),
-                transform: Transform::from_translation(vec3(x, y, 1.0))
-                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-                ..default()
-            },
-            TextureAtlas {
-                layout: game_decorations.atlas_layout.clone().unwrap(),
-                index: rng.gen_range(0..=1),
-            },
-            GameEntity,
-        ));
-    }
+fn update_run_stats(mut run_stats: ResMut<RunStats>, time: Res<Time>) {
+    run_stats.elapsed += time.delta_seconds();
+    run_stats.wave = (run_stats.elapsed / WAVE_DURATION) as u32 + 1;
 }
 
 fn despawn_all_game_entities(